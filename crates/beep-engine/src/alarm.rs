@@ -0,0 +1,223 @@
+// 闹钟：按本地墙上时间计算下一次响铃时刻
+// 所有换算都经过时区，夏令时切换当天也能在正确的时刻响
+
+use beep_protocol::{Alarm, AlarmRepeat};
+use chrono::{DateTime, Datelike, Days, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone};
+
+// 最多向后查找的天数，足够覆盖所有按周或按月重复的规则
+const SEARCH_DAYS: u64 = 400;
+
+// 校验闹钟设置，返回可以直接显示给用户的错误
+pub fn validate(alarm: &Alarm) -> Result<(), String> {
+    if alarm.hour > 23 || alarm.minute > 59 {
+        return Err(format!("无效的闹钟时间: {:02}:{:02}", alarm.hour, alarm.minute));
+    }
+    match &alarm.repeat {
+        AlarmRepeat::Once { year, month, day } => {
+            if NaiveDate::from_ymd_opt(*year, *month, *day).is_none() {
+                return Err(format!("无效的日期: {}-{:02}-{:02}", year, month, day));
+            }
+        }
+        AlarmRepeat::Daily => {}
+        AlarmRepeat::Weekly { weekdays } => {
+            if weekdays.is_empty() || weekdays.iter().any(|weekday| !(1..=7).contains(weekday)) {
+                return Err("每周重复至少需要选择一天".to_string());
+            }
+        }
+        AlarmRepeat::MonthlyDay { day } => {
+            if !(1..=31).contains(day) {
+                return Err(format!("无效的日期: 每月{}日", day));
+            }
+        }
+        AlarmRepeat::MonthlyWeekday { week, weekday } => {
+            if !(matches!(week, 1..=4) || *week == -1) || !(1..=7).contains(weekday) {
+                return Err("无效的每月重复规则".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+// 某月的天数
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(28, |last| last.day())
+}
+
+// 这一天是否符合重复规则
+fn matches(repeat: &AlarmRepeat, date: NaiveDate) -> bool {
+    let weekday = date.weekday().number_from_monday();
+    match repeat {
+        AlarmRepeat::Once { year, month, day } => NaiveDate::from_ymd_opt(*year, *month, *day) == Some(date),
+        AlarmRepeat::Daily => true,
+        AlarmRepeat::Weekly { weekdays } => weekdays.contains(&weekday),
+        // 没有这一天的月份（例如 2 月 30 日）改在当月最后一天
+        AlarmRepeat::MonthlyDay { day } => date.day() == (*day).min(days_in_month(date.year(), date.month())),
+        AlarmRepeat::MonthlyWeekday { week, weekday: target } => {
+            if weekday != *target {
+                return false;
+            }
+            if *week == -1 {
+                date.day() + 7 > days_in_month(date.year(), date.month())
+            } else {
+                (date.day() - 1) / 7 + 1 == *week as u32
+            }
+        }
+    }
+}
+
+// 把本地日期时间换算成时区中的时刻
+// 夏令时开始时跳过的时间不存在，改在切换后的第一个有效分钟响；回拨时重复的时间取第一次
+pub(crate) fn resolve<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    let mut candidate = local;
+    // 时区切换最多跳过几个小时，逐分钟向后找即可
+    for _ in 0..=24 * 60 {
+        match tz.from_local_datetime(&candidate) {
+            LocalResult::Single(time) => return Some(time),
+            LocalResult::Ambiguous(earliest, _) => return Some(earliest),
+            LocalResult::None => candidate += TimeDelta::minutes(1),
+        }
+    }
+    None
+}
+
+// 严格晚于 after 的下一次响铃时刻，不会再响（例如一次性闹钟已经过去）时返回空
+pub fn next_occurrence<Tz: TimeZone>(alarm: &Alarm, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let time = NaiveTime::from_hms_opt(alarm.hour, alarm.minute, 0)?;
+    let tz = after.timezone();
+    // 一次性闹钟直接从那一天找起，其余规则从今天找起
+    let start = match &alarm.repeat {
+        AlarmRepeat::Once { year, month, day } => NaiveDate::from_ymd_opt(*year, *month, *day)?.max(after.date_naive()),
+        _ => after.date_naive(),
+    };
+    // 从前一天开始查找：夏令时跳过的时间可能顺延到第二天
+    let first = start.pred_opt().unwrap_or(start);
+    (0..SEARCH_DAYS)
+        .filter_map(|offset| first.checked_add_days(Days::new(offset)))
+        .filter(|date| matches(&alarm.repeat, *date))
+        .filter_map(|date| resolve(&tz, date.and_time(time)))
+        .find(|fire| fire > after)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Utc, Weekday};
+    use chrono_tz::Europe::Berlin;
+    use chrono_tz::Tz;
+
+    use super::*;
+
+    fn alarm(hour: u32, minute: u32, repeat: AlarmRepeat) -> Alarm {
+        Alarm {
+            id: String::new(),
+            label: String::new(),
+            hour,
+            minute,
+            repeat,
+            enabled: true,
+            next_fire_unix_ms: None,
+        }
+    }
+
+    fn berlin(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        Berlin.with_ymd_and_hms(year, month, day, hour, minute, 0).single().unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(alarm: &Alarm, after: &DateTime<Tz>) -> DateTime<Utc> {
+        next_occurrence(alarm, after).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn spring_forward_gap_rings_after_switch() {
+        // 2026-03-29 柏林 02:00 直接跳到 03:00，02:30 不存在
+        let alarm = alarm(2, 30, AlarmRepeat::Daily);
+        let fire = next_occurrence(&alarm, &berlin(2026, 3, 28, 12, 0)).unwrap();
+        assert_eq!(fire.with_timezone(&Utc), utc(2026, 3, 29, 1, 0));
+        assert_eq!(fire.naive_local(), NaiveDate::from_ymd_opt(2026, 3, 29).unwrap().and_hms_opt(3, 0, 0).unwrap());
+        // 第二天恢复正常
+        assert_eq!(next(&alarm, &fire), utc(2026, 3, 30, 0, 30));
+    }
+
+    #[test]
+    fn fall_back_fold_rings_once() {
+        // 2026-10-25 柏林 03:00 回拨到 02:00，02:30 出现两次，只在第一次响
+        let alarm = alarm(2, 30, AlarmRepeat::Daily);
+        let fire = next_occurrence(&alarm, &berlin(2026, 10, 24, 12, 0)).unwrap();
+        assert_eq!(fire.with_timezone(&Utc), utc(2026, 10, 25, 0, 30));
+        assert_eq!(next(&alarm, &fire), utc(2026, 10, 26, 1, 30));
+    }
+
+    #[test]
+    fn daily_keeps_wall_time_across_dst() {
+        let alarm = alarm(9, 0, AlarmRepeat::Daily);
+        assert_eq!(next(&alarm, &berlin(2026, 10, 24, 10, 0)), utc(2026, 10, 25, 8, 0));
+        assert_eq!(next(&alarm, &berlin(2026, 10, 24, 8, 0)), utc(2026, 10, 24, 7, 0));
+    }
+
+    #[test]
+    fn next_is_strictly_after() {
+        let alarm = alarm(9, 0, AlarmRepeat::Daily);
+        assert_eq!(next(&alarm, &berlin(2026, 10, 17, 9, 0)), utc(2026, 10, 18, 7, 0));
+    }
+
+    #[test]
+    fn weekly_rules() {
+        // 2026-10-16 是星期五
+        assert_eq!(berlin(2026, 10, 16, 0, 0).weekday(), Weekday::Fri);
+        let workdays = alarm(7, 0, AlarmRepeat::Weekly { weekdays: vec![1, 2, 3, 4, 5] });
+        assert_eq!(next(&workdays, &berlin(2026, 10, 16, 6, 0)), utc(2026, 10, 16, 5, 0));
+        assert_eq!(next(&workdays, &berlin(2026, 10, 16, 8, 0)), utc(2026, 10, 19, 5, 0));
+        let sunday = alarm(7, 0, AlarmRepeat::Weekly { weekdays: vec![7] });
+        // 跨过夏令时结束的那个星期天
+        assert_eq!(next(&sunday, &berlin(2026, 10, 19, 8, 0)), utc(2026, 10, 25, 6, 0));
+    }
+
+    #[test]
+    fn monthly_day_on_31st_uses_last_day_of_short_months() {
+        let alarm = alarm(8, 0, AlarmRepeat::MonthlyDay { day: 31 });
+        let november = next_occurrence(&alarm, &berlin(2026, 11, 1, 0, 0)).unwrap();
+        assert_eq!(november.with_timezone(&Utc), utc(2026, 11, 30, 7, 0));
+        let december = next_occurrence(&alarm, &november).unwrap();
+        assert_eq!(december.with_timezone(&Utc), utc(2026, 12, 31, 7, 0));
+        assert_eq!(next(&alarm, &berlin(2027, 2, 1, 0, 0)), utc(2027, 2, 28, 7, 0));
+        assert_eq!(next(&alarm, &berlin(2028, 2, 1, 0, 0)), utc(2028, 2, 29, 7, 0));
+    }
+
+    #[test]
+    fn monthly_weekday_rules() {
+        // 2026 年 11 月第二个星期二是 10 日
+        let second_tuesday = alarm(8, 0, AlarmRepeat::MonthlyWeekday { week: 2, weekday: 2 });
+        assert_eq!(next(&second_tuesday, &berlin(2026, 10, 20, 0, 0)), utc(2026, 11, 10, 7, 0));
+        // 2026 年 10 月最后一个星期五是 30 日
+        let last_friday = alarm(17, 0, AlarmRepeat::MonthlyWeekday { week: -1, weekday: 5 });
+        assert_eq!(next(&last_friday, &berlin(2026, 10, 17, 0, 0)), utc(2026, 10, 30, 16, 0));
+        let first_monday = alarm(8, 0, AlarmRepeat::MonthlyWeekday { week: 1, weekday: 1 });
+        assert_eq!(next(&first_monday, &berlin(2026, 10, 17, 0, 0)), utc(2026, 11, 2, 7, 0));
+    }
+
+    #[test]
+    fn once_alarm_does_not_repeat() {
+        let alarm = alarm(8, 0, AlarmRepeat::Once { year: 2026, month: 12, day: 24 });
+        let fire = next_occurrence(&alarm, &berlin(2026, 10, 17, 0, 0)).unwrap();
+        assert_eq!(fire.with_timezone(&Utc), utc(2026, 12, 24, 7, 0));
+        assert!(next_occurrence(&alarm, &fire).is_none());
+    }
+
+    #[test]
+    fn validate_rejects_invalid_rules() {
+        assert!(validate(&alarm(24, 0, AlarmRepeat::Daily)).is_err());
+        assert!(validate(&alarm(8, 60, AlarmRepeat::Daily)).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::Once { year: 2027, month: 2, day: 29 })).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::Weekly { weekdays: vec![] })).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::Weekly { weekdays: vec![0] })).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::MonthlyDay { day: 32 })).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::MonthlyWeekday { week: 5, weekday: 1 })).is_err());
+        assert!(validate(&alarm(8, 0, AlarmRepeat::MonthlyWeekday { week: -1, weekday: 7 })).is_ok());
+    }
+}
@@ -0,0 +1,837 @@
+// 日历文件（iCalendar / .ics）：读取 VEVENT，按 RRULE、EXDATE 和 RECURRENCE-ID 展开为具体的开始时刻
+// 只实现会议提醒需要的部分：全天事件没有开始时刻，不生成提醒；看不懂的重复规则或时区跳过整个事件并计数
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
+
+use crate::alarm::resolve;
+
+// 展开重复规则时最多检查的周期数，防止永远匹配不到的规则卡住
+const MAX_PERIODS: u32 = 100_000;
+
+// 时间所在的时区
+#[derive(Clone, Debug, PartialEq)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    // VTIMEZONE 中没有夏令时的自定义时区
+    Fixed(FixedOffset),
+    // 不带时区的“浮动”时间按本地时间理解
+    Floating,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct EventTime {
+    local: NaiveDateTime,
+    zone: Zone,
+}
+
+impl EventTime {
+    fn unix_ms(&self) -> Option<i64> {
+        match &self.zone {
+            Zone::Utc => Some(self.local.and_utc().timestamp_millis()),
+            Zone::Named(tz) => resolve(tz, self.local).map(|time| time.timestamp_millis()),
+            Zone::Fixed(offset) => resolve(offset, self.local).map(|time| time.timestamp_millis()),
+            Zone::Floating => resolve(&Local, self.local).map(|time| time.timestamp_millis()),
+        }
+    }
+
+    // 同一时区中另一天的同一时刻
+    fn on(&self, date: NaiveDate) -> EventTime {
+        EventTime { local: date.and_time(self.local.time()), zone: self.zone.clone() }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// RRULE 中支持的部分
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<i64>,
+    // (序号, 星期)，序号为 0 表示每个这样的星期几，负数从月末倒数
+    by_day: Vec<(i32, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+// 日历中的一个事件，重复事件的例外实例也是单独的事件
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    start: EventTime,
+    rule: Option<Rule>,
+    // 被排除的实例的开始时刻（Unix 毫秒）
+    exdates: Vec<i64>,
+    // 例外实例替换的原始开始时刻
+    recurrence_id: Option<i64>,
+}
+
+// 解析结果：能生成提醒的事件，以及因为全天、已取消或规则不受支持而跳过的事件数
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Calendar {
+    pub events: Vec<CalendarEvent>,
+    pub skipped: u32,
+}
+
+// 事件的一次具体发生
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub uid: String,
+    pub summary: String,
+    pub start_unix_ms: i64,
+}
+
+// 一行内容：名称、参数和值
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, String)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+// 续行以空格或制表符开头，接到上一行末尾
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// NAME;PARAM=VALUE;PARAM="VALUE":值，引号内的分号和冒号不起分隔作用
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    let mut quoted = false;
+    let mut colon = None;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                colon = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: &line[colon + 1..] })
+}
+
+// 文本值的转义：\n、\,、\; 和 \\
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+// 时区名称对应的时区：IANA 名称（有的日历程序会加上 /mozilla.org/... 之类的前缀，依次去掉前缀再试），
+// 或者 Outlook / Exchange 使用的 Windows 时区名称
+fn known_zone(name: &str) -> Option<Zone> {
+    let name = name.trim();
+    if let Some((_, iana)) = WINDOWS_ZONES.iter().find(|(windows, _)| windows.eq_ignore_ascii_case(name)) {
+        return Tz::from_str(iana).ok().map(Zone::Named);
+    }
+    let mut name = name.trim_start_matches('/');
+    loop {
+        if let Ok(tz) = Tz::from_str(name) {
+            return Some(Zone::Named(tz));
+        }
+        name = name.split_once('/')?.1;
+    }
+}
+
+// TZID 对应的时区，文件中的 VTIMEZONE 可以定义自己的名称；没有 TZID 时为浮动时间
+// 无法确定的时区返回空，调用方跳过该事件，而不是按本地时间提醒在错误的时刻
+fn zone(tzid: Option<&str>, timezones: &BTreeMap<String, Zone>) -> Option<Zone> {
+    let Some(tzid) = tzid else {
+        return Some(Zone::Floating);
+    };
+    known_zone(tzid).or_else(|| timezones.get(tzid.trim()).cloned())
+}
+
+// 文件中的一个 VTIMEZONE
+#[derive(Default)]
+struct TimezoneDefinition {
+    tzid: Option<String>,
+    // 许多日历程序在 X-LIC-LOCATION 中给出对应的 IANA 名称
+    location: Option<String>,
+    standard_offsets: Vec<FixedOffset>,
+    daylight: bool,
+}
+
+impl TimezoneDefinition {
+    // 能确定的时区：对应的 IANA 名称，或者没有夏令时、只有一个偏移量的固定时区
+    fn zone(&self) -> Option<Zone> {
+        if let Some(zone) = self.location.as_deref().and_then(known_zone) {
+            return Some(zone);
+        }
+        match self.standard_offsets.as_slice() {
+            [offset, rest @ ..] if !self.daylight && rest.iter().all(|other| other == offset) => Some(Zone::Fixed(*offset)),
+            _ => None,
+        }
+    }
+}
+
+// UTC 偏移量：+0800、-0500、+053000
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let (sign, digits) = match value.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if !matches!(digits.len(), 4 | 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[0..2].parse().ok()?;
+    let minutes: i32 = digits[2..4].parse().ok()?;
+    let seconds: i32 = digits.get(4..6).map_or(Some(0), |seconds| seconds.parse().ok())?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+// 日期时间值，返回空表示只有日期（全天）或格式不对
+fn parse_time(value: &str, zone: Zone) -> Option<EventTime> {
+    let value = value.trim();
+    let (value, zone) = match value.strip_suffix('Z') {
+        Some(value) => (value, Zone::Utc),
+        None => (value, zone),
+    };
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some(EventTime { local, zone })
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d").ok()
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// 解析 RRULE；出现不支持的部分（按小时重复、BYSETPOS 等）时返回空，由调用方跳过该事件
+fn parse_rule(value: &str, start: &EventTime) -> Option<Rule> {
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => {
+                rule.until = Some(match parse_time(value, start.zone.clone()) {
+                    Some(until) => until.unix_ms()?,
+                    // 只有日期时包括那一整天
+                    None => {
+                        let next_day = parse_date(value)?.succ_opt()?.and_time(NaiveTime::MIN);
+                        EventTime { local: next_day, zone: start.zone.clone() }.unix_ms()? - 1
+                    }
+                })
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    let day = day.trim().to_ascii_uppercase();
+                    if !day.is_ascii() {
+                        return None;
+                    }
+                    let (ordinal, code) = day.split_at(day.len().checked_sub(2)?);
+                    let ordinal = if ordinal.is_empty() { 0 } else { ordinal.parse().ok()? };
+                    rule.by_day.push((ordinal, parse_weekday(code)?));
+                }
+            }
+            "BYMONTHDAY" => {
+                for day in value.split(',') {
+                    rule.by_month_day.push(day.trim().parse().ok().filter(|day: &i32| (1..=31).contains(&day.abs()))?);
+                }
+            }
+            "BYMONTH" => {
+                for month in value.split(',') {
+                    rule.by_month.push(month.trim().parse().ok().filter(|month| (1..=12).contains(month))?);
+                }
+            }
+            // 默认一周从周一开始，其他起始日只影响按多周重复的少数规则，这里不区分
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+// 解析日历文本；文件本身不是日历时返回错误，个别无法理解的事件只计入 skipped
+pub fn parse(text: &str) -> Result<Calendar, String> {
+    let lines = unfold(text);
+    if !lines.iter().any(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("不是 iCalendar 文件：缺少 BEGIN:VCALENDAR".to_string());
+    }
+    // VTIMEZONE 可能写在事件之后，先收集所有事件的属性，读完整个文件再解析事件
+    let mut events: Vec<Vec<Property>> = Vec::new();
+    let mut timezones = BTreeMap::new();
+    // 当前所在的组件，VEVENT 中嵌套的 VALARM 等组件的属性不属于事件
+    let mut components: Vec<String> = Vec::new();
+    let mut properties: Vec<Property> = Vec::new();
+    let mut timezone = TimezoneDefinition::default();
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_ascii_uppercase();
+                if component == "DAYLIGHT" && components.last().is_some_and(|parent| parent == "VTIMEZONE") {
+                    timezone.daylight = true;
+                }
+                components.push(component);
+            }
+            "END" => match components.pop().as_deref() {
+                Some("VEVENT") => events.push(std::mem::take(&mut properties)),
+                Some("VTIMEZONE") => {
+                    let definition = std::mem::take(&mut timezone);
+                    if let (Some(tzid), Some(zone)) = (&definition.tzid, definition.zone()) {
+                        timezones.insert(tzid.clone(), zone);
+                    }
+                }
+                _ => {}
+            },
+            _ => match components.iter().rev().take(2).map(String::as_str).collect::<Vec<_>>().as_slice() {
+                ["VEVENT", ..] => properties.push(property),
+                ["VTIMEZONE", ..] if property.name == "TZID" => timezone.tzid = Some(property.value.trim().to_string()),
+                ["VTIMEZONE", ..] if property.name == "X-LIC-LOCATION" => {
+                    timezone.location = Some(property.value.trim().to_string())
+                }
+                ["STANDARD", "VTIMEZONE"] if property.name == "TZOFFSETTO" => {
+                    timezone.standard_offsets.extend(parse_offset(property.value))
+                }
+                _ => {}
+            },
+        }
+    }
+    let mut calendar = Calendar::default();
+    for properties in &events {
+        match event(properties, &timezones) {
+            Some(event) => calendar.events.push(event),
+            None => calendar.skipped += 1,
+        }
+    }
+    // 例外实例取代原来的那一次
+    let overrides: Vec<(String, i64)> = calendar
+        .events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone(), event.recurrence_id?)))
+        .collect();
+    for event in calendar.events.iter_mut().filter(|event| event.recurrence_id.is_none()) {
+        event.exdates.extend(overrides.iter().filter(|(uid, _)| *uid == event.uid).map(|(_, start)| *start));
+    }
+    Ok(calendar)
+}
+
+// 由一个 VEVENT 的属性组成事件；全天、已取消、时区无法确定或规则不受支持时返回空
+fn event(properties: &[Property], timezones: &BTreeMap<String, Zone>) -> Option<CalendarEvent> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+    if find("STATUS").is_some_and(|status| status.value.trim().eq_ignore_ascii_case("CANCELLED")) {
+        return None;
+    }
+    let dtstart = find("DTSTART")?;
+    if dtstart.param("VALUE").is_some_and(|value| value.eq_ignore_ascii_case("DATE")) {
+        return None;
+    }
+    let start = parse_time(dtstart.value, zone(dtstart.param("TZID"), timezones)?)?;
+    let rule = match find("RRULE") {
+        Some(rrule) => Some(parse_rule(rrule.value, &start)?),
+        None => None,
+    };
+    let mut exdates = Vec::new();
+    for property in properties.iter().filter(|property| property.name == "EXDATE") {
+        let zone = zone(property.param("TZID"), timezones)?;
+        exdates.extend(property.value.split(',').filter_map(|value| parse_time(value, zone.clone())?.unix_ms()));
+    }
+    let recurrence_id = match find("RECURRENCE-ID") {
+        Some(property) => parse_time(property.value, zone(property.param("TZID"), timezones)?)?.unix_ms(),
+        None => None,
+    };
+    let uid = find("UID").map(|uid| uid.value.trim().to_string()).unwrap_or_default();
+    let summary = find("SUMMARY").map(|summary| unescape(summary.value.trim())).unwrap_or_default();
+    Some(CalendarEvent {
+        summary: if summary.is_empty() { "日历事件".to_string() } else { summary },
+        uid,
+        start,
+        rule,
+        exdates,
+        recurrence_id,
+    })
+}
+
+// 某月的天数
+fn days_in_month(year: i32, month: u32) -> u32 {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(28, |last| last.day())
+}
+
+// 一个月中符合 BYMONTHDAY / BYDAY 的日期，都没有时取 default_day 这一天（没有这一天的月份跳过）
+fn month_dates(rule: &Rule, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+    let length = days_in_month(year, month);
+    let dates = (1..=length).filter_map(|day| NaiveDate::from_ymd_opt(year, month, day));
+    if rule.by_month_day.is_empty() && rule.by_day.is_empty() {
+        return dates.filter(|date| date.day() == default_day).collect();
+    }
+    dates
+        .filter(|date| {
+            let day = date.day() as i32;
+            let month_day = rule.by_month_day.is_empty()
+                || rule.by_month_day.iter().any(|target| *target == day || *target == day - length as i32 - 1);
+            // 第 n 个星期几：正数从月初数，负数从月末数
+            let nth = (day - 1) / 7 + 1;
+            let nth_from_end = -((length as i32 - day) / 7 + 1);
+            let weekday = rule.by_day.is_empty()
+                || rule.by_day.iter().any(|(ordinal, weekday)| {
+                    *weekday == date.weekday() && (*ordinal == 0 || *ordinal == nth || *ordinal == nth_from_end)
+                });
+            month_day && weekday
+        })
+        .collect()
+}
+
+// 第 period 个周期（从 0 开始）内的候选日期，按时间先后排列
+fn period_dates(rule: &Rule, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+    let step = period.saturating_mul(rule.interval);
+    let in_month = |date: &NaiveDate| rule.by_month.is_empty() || rule.by_month.contains(&date.month());
+    match rule.frequency {
+        Frequency::Daily => start
+            .checked_add_days(Days::new(u64::from(step)))
+            .filter(in_month)
+            .filter(|date| rule.by_day.is_empty() || rule.by_day.iter().any(|(_, weekday)| *weekday == date.weekday()))
+            .filter(|date| rule.by_month_day.is_empty() || rule.by_month_day.contains(&(date.day() as i32)))
+            .into_iter()
+            .collect(),
+        Frequency::Weekly => {
+            let monday = start - Days::new(u64::from(start.weekday().num_days_from_monday()));
+            let Some(monday) = monday.checked_add_days(Days::new(u64::from(step) * 7)) else {
+                return Vec::new();
+            };
+            (0..7)
+                .filter_map(|offset| monday.checked_add_days(Days::new(offset)))
+                .filter(|date| match rule.by_day.is_empty() {
+                    true => date.weekday() == start.weekday(),
+                    false => rule.by_day.iter().any(|(_, weekday)| *weekday == date.weekday()),
+                })
+                .filter(in_month)
+                .collect()
+        }
+        Frequency::Monthly => {
+            let Some(first) = start.with_day(1).and_then(|first| first.checked_add_months(Months::new(step))) else {
+                return Vec::new();
+            };
+            if !in_month(&first) {
+                return Vec::new();
+            }
+            month_dates(rule, first.year(), first.month(), start.day())
+        }
+        // 按年重复时，BYDAY / BYMONTHDAY 在 BYMONTH 指定的月份（默认为开始的月份）内展开
+        Frequency::Yearly => {
+            let year = start.year() + step.min(10_000) as i32;
+            let months = if rule.by_month.is_empty() { vec![start.month()] } else { rule.by_month.clone() };
+            let mut dates: Vec<NaiveDate> =
+                months.into_iter().flat_map(|month| month_dates(rule, year, month, start.day())).collect();
+            dates.sort();
+            dates
+        }
+    }
+}
+
+impl CalendarEvent {
+    // 开始时刻落在 [from_unix_ms, to_unix_ms) 内的各次发生
+    fn starts_between(&self, from_unix_ms: i64, to_unix_ms: i64) -> Vec<i64> {
+        let Some(first) = self.start.unix_ms() else {
+            return Vec::new();
+        };
+        let Some(rule) = &self.rule else {
+            return if (from_unix_ms..to_unix_ms).contains(&first) { vec![first] } else { Vec::new() };
+        };
+        let start_date = self.start.local.date();
+        let mut starts = Vec::new();
+        // COUNT 从第一次开始计数，被 EXDATE 排除的实例也算在内
+        let mut generated = 0;
+        for period in 0..MAX_PERIODS {
+            for date in period_dates(rule, start_date, period) {
+                let Some(start) = self.start.on(date).unix_ms() else {
+                    continue;
+                };
+                if start < first {
+                    continue;
+                }
+                if rule.count.is_some_and(|count| generated >= count)
+                    || rule.until.is_some_and(|until| start > until)
+                    || start >= to_unix_ms
+                {
+                    return starts;
+                }
+                generated += 1;
+                if start >= from_unix_ms && !self.exdates.contains(&start) {
+                    starts.push(start);
+                }
+            }
+        }
+        starts
+    }
+}
+
+// 所有事件在 [from_unix_ms, to_unix_ms) 内开始的各次发生，按开始时刻排列
+pub fn occurrences(events: &[CalendarEvent], from_unix_ms: i64, to_unix_ms: i64) -> Vec<Occurrence> {
+    let mut occurrences: Vec<Occurrence> = events
+        .iter()
+        .flat_map(|event| {
+            event.starts_between(from_unix_ms, to_unix_ms).into_iter().map(|start_unix_ms| Occurrence {
+                uid: event.uid.clone(),
+                summary: event.summary.clone(),
+                start_unix_ms,
+            })
+        })
+        .collect();
+    occurrences.sort_by_key(|occurrence| occurrence.start_unix_ms);
+    occurrences
+}
+
+// Windows 时区名称到 IANA 名称，取自 CLDR windowsZones 中各时区的代表地区（001）
+const WINDOWS_ZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC-11", "Etc/GMT+11"),
+    ("Aleutian Standard Time", "America/Adak"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Marquesas Standard Time", "Pacific/Marquesas"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("UTC-09", "Etc/GMT+9"),
+    ("Pacific Standard Time (Mexico)", "America/Tijuana"),
+    ("UTC-08", "Etc/GMT+8"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time (Mexico)", "America/Mazatlan"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Yukon Standard Time", "America/Whitehorse"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Easter Island Standard Time", "Pacific/Easter"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Eastern Standard Time (Mexico)", "America/Cancun"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("Haiti Standard Time", "America/Port-au-Prince"),
+    ("Cuba Standard Time", "America/Havana"),
+    ("US Eastern Standard Time", "America/Indiana/Indianapolis"),
+    ("Turks And Caicos Standard Time", "America/Grand_Turk"),
+    ("Paraguay Standard Time", "America/Asuncion"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Venezuela Standard Time", "America/Caracas"),
+    ("Central Brazilian Standard Time", "America/Cuiaba"),
+    ("SA Western Standard Time", "America/La_Paz"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("Tocantins Standard Time", "America/Araguaina"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("SA Eastern Standard Time", "America/Cayenne"),
+    ("Argentina Standard Time", "America/Argentina/Buenos_Aires"),
+    ("Greenland Standard Time", "America/Nuuk"),
+    ("Montevideo Standard Time", "America/Montevideo"),
+    ("Magallanes Standard Time", "America/Punta_Arenas"),
+    ("Saint Pierre Standard Time", "America/Miquelon"),
+    ("Bahia Standard Time", "America/Bahia"),
+    ("UTC-02", "Etc/GMT+2"),
+    ("Azores Standard Time", "Atlantic/Azores"),
+    ("Cape Verde Standard Time", "Atlantic/Cape_Verde"),
+    ("UTC", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("Sao Tome Standard Time", "Africa/Sao_Tome"),
+    ("Morocco Standard Time", "Africa/Casablanca"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("W. Central Africa Standard Time", "Africa/Lagos"),
+    ("Jordan Standard Time", "Asia/Amman"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Middle East Standard Time", "Asia/Beirut"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("Syria Standard Time", "Asia/Damascus"),
+    ("West Bank Standard Time", "Asia/Hebron"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("FLE Standard Time", "Europe/Kiev"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("South Sudan Standard Time", "Africa/Juba"),
+    ("Kaliningrad Standard Time", "Europe/Kaliningrad"),
+    ("Sudan Standard Time", "Africa/Khartoum"),
+    ("Libya Standard Time", "Africa/Tripoli"),
+    ("Namibia Standard Time", "Africa/Windhoek"),
+    ("Arabic Standard Time", "Asia/Baghdad"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Belarus Standard Time", "Europe/Minsk"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("E. Africa Standard Time", "Africa/Nairobi"),
+    ("Volgograd Standard Time", "Europe/Volgograd"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Astrakhan Standard Time", "Europe/Astrakhan"),
+    ("Azerbaijan Standard Time", "Asia/Baku"),
+    ("Russia Time Zone 3", "Europe/Samara"),
+    ("Mauritius Standard Time", "Indian/Mauritius"),
+    ("Saratov Standard Time", "Europe/Saratov"),
+    ("Georgian Standard Time", "Asia/Tbilisi"),
+    ("Caucasus Standard Time", "Asia/Yerevan"),
+    ("Afghanistan Standard Time", "Asia/Kabul"),
+    ("West Asia Standard Time", "Asia/Tashkent"),
+    ("Ekaterinburg Standard Time", "Asia/Yekaterinburg"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("Qyzylorda Standard Time", "Asia/Qyzylorda"),
+    ("India Standard Time", "Asia/Kolkata"),
+    ("Sri Lanka Standard Time", "Asia/Colombo"),
+    ("Nepal Standard Time", "Asia/Kathmandu"),
+    ("Central Asia Standard Time", "Asia/Almaty"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("Omsk Standard Time", "Asia/Omsk"),
+    ("Myanmar Standard Time", "Asia/Yangon"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("Altai Standard Time", "Asia/Barnaul"),
+    ("W. Mongolia Standard Time", "Asia/Hovd"),
+    ("North Asia Standard Time", "Asia/Krasnoyarsk"),
+    ("N. Central Asia Standard Time", "Asia/Novosibirsk"),
+    ("Tomsk Standard Time", "Asia/Tomsk"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("North Asia East Standard Time", "Asia/Irkutsk"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Ulaanbaatar Standard Time", "Asia/Ulaanbaatar"),
+    ("Aus Central W. Standard Time", "Australia/Eucla"),
+    ("Transbaikal Standard Time", "Asia/Chita"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("North Korea Standard Time", "Asia/Pyongyang"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("Yakutsk Standard Time", "Asia/Yakutsk"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("West Pacific Standard Time", "Pacific/Port_Moresby"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("Vladivostok Standard Time", "Asia/Vladivostok"),
+    ("Lord Howe Standard Time", "Australia/Lord_Howe"),
+    ("Bougainville Standard Time", "Pacific/Bougainville"),
+    ("Russia Time Zone 10", "Asia/Srednekolymsk"),
+    ("Magadan Standard Time", "Asia/Magadan"),
+    ("Norfolk Standard Time", "Pacific/Norfolk"),
+    ("Sakhalin Standard Time", "Asia/Sakhalin"),
+    ("Central Pacific Standard Time", "Pacific/Guadalcanal"),
+    ("Russia Time Zone 11", "Asia/Kamchatka"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("UTC+12", "Etc/GMT-12"),
+    ("Fiji Standard Time", "Pacific/Fiji"),
+    ("Chatham Islands Standard Time", "Pacific/Chatham"),
+    ("UTC+13", "Etc/GMT-13"),
+    ("Tonga Standard Time", "Pacific/Tongatapu"),
+    ("Samoa Standard Time", "Pacific/Apia"),
+    ("Line Islands Standard Time", "Pacific/Kiritimati"),
+];
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap().timestamp_millis()
+    }
+
+    fn calendar(body: &str) -> Calendar {
+        parse(&format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}\r\nEND:VCALENDAR\r\n", body.trim())).unwrap()
+    }
+
+    // 2026 年 10 月到 11 月初之间各次发生的开始时刻
+    fn starts(calendar: &Calendar) -> Vec<i64> {
+        occurrences(&calendar.events, utc(2026, 10, 1, 0, 0), utc(2026, 11, 15, 0, 0))
+            .into_iter()
+            .map(|occurrence| occurrence.start_unix_ms)
+            .collect()
+    }
+
+    #[test]
+    fn rrule_count_limits_occurrences() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART:20261019T090000Z\nRRULE:FREQ=DAILY;COUNT=3\nEND:VEVENT",
+        );
+        assert_eq!(
+            starts(&calendar),
+            vec![utc(2026, 10, 19, 9, 0), utc(2026, 10, 20, 9, 0), utc(2026, 10, 21, 9, 0)]
+        );
+    }
+
+    #[test]
+    fn rrule_until_is_inclusive() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART;TZID=Europe/Berlin:20261019T093000\n\
+             RRULE:FREQ=DAILY;UNTIL=20261021T073000Z\nEND:VEVENT",
+        );
+        assert_eq!(
+            starts(&calendar),
+            vec![utc(2026, 10, 19, 7, 30), utc(2026, 10, 20, 7, 30), utc(2026, 10, 21, 7, 30)]
+        );
+    }
+
+    #[test]
+    fn exdate_removes_instance() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART:20261019T090000Z\nRRULE:FREQ=DAILY;COUNT=4\n\
+             EXDATE:20261020T090000Z,20261022T090000Z\nEND:VEVENT",
+        );
+        assert_eq!(starts(&calendar), vec![utc(2026, 10, 19, 9, 0), utc(2026, 10, 21, 9, 0)]);
+    }
+
+    #[test]
+    fn recurrence_id_override_replaces_instance() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nSUMMARY:站会\nDTSTART:20261019T090000Z\nRRULE:FREQ=DAILY;COUNT=3\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:a\nSUMMARY:站会（改期）\nRECURRENCE-ID:20261020T090000Z\n\
+             DTSTART:20261020T110000Z\nEND:VEVENT",
+        );
+        let occurrences = occurrences(&calendar.events, utc(2026, 10, 1, 0, 0), utc(2026, 11, 1, 0, 0));
+        let found: Vec<(&str, i64)> =
+            occurrences.iter().map(|occurrence| (occurrence.summary.as_str(), occurrence.start_unix_ms)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("站会", utc(2026, 10, 19, 9, 0)),
+                ("站会（改期）", utc(2026, 10, 20, 11, 0)),
+                ("站会", utc(2026, 10, 21, 9, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_event_keeps_local_time_across_dst() {
+        // 2026-10-25 柏林从夏令时（UTC+2）切回标准时间（UTC+1）
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART;TZID=Europe/Berlin:20261019T093000\nRRULE:FREQ=WEEKLY;COUNT=2\nEND:VEVENT",
+        );
+        assert_eq!(starts(&calendar), vec![utc(2026, 10, 19, 7, 30), utc(2026, 10, 26, 8, 30)]);
+    }
+
+    #[test]
+    fn windows_zone_names_map_to_iana() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART;TZID=\"W. Europe Standard Time\":20261026T093000\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:b\nDTSTART;TZID=Eastern Standard Time:20261026T093000\nEND:VEVENT",
+        );
+        assert_eq!(calendar.skipped, 0);
+        assert_eq!(starts(&calendar), vec![utc(2026, 10, 26, 8, 30), utc(2026, 10, 26, 13, 30)]);
+    }
+
+    #[test]
+    fn every_windows_zone_is_known() {
+        for (windows, iana) in WINDOWS_ZONES {
+            assert!(Tz::from_str(iana).is_ok(), "{} -> {}", windows, iana);
+        }
+    }
+
+    #[test]
+    fn vtimezone_location_resolves_custom_tzid() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\nUID:a\nDTSTART;TZID=Office:20261026T093000\nEND:VEVENT\n\
+             BEGIN:VTIMEZONE\nTZID:Office\nX-LIC-LOCATION:Asia/Tokyo\n\
+             BEGIN:STANDARD\nDTSTART:19700101T000000\nTZOFFSETFROM:+0900\nTZOFFSETTO:+0900\nEND:STANDARD\n\
+             END:VTIMEZONE",
+        );
+        assert_eq!(starts(&calendar), vec![utc(2026, 10, 26, 0, 30)]);
+    }
+
+    #[test]
+    fn vtimezone_without_daylight_is_fixed_offset() {
+        let calendar = calendar(
+            "BEGIN:VTIMEZONE\nTZID:(UTC+05:30) Chennai\n\
+             BEGIN:STANDARD\nDTSTART:16010101T000000\nTZOFFSETFROM:+0530\nTZOFFSETTO:+0530\nEND:STANDARD\n\
+             END:VTIMEZONE\n\
+             BEGIN:VEVENT\nUID:a\nDTSTART;TZID=\"(UTC+05:30) Chennai\":20261026T093000\nEND:VEVENT",
+        );
+        assert_eq!(starts(&calendar), vec![utc(2026, 10, 26, 4, 0)]);
+    }
+
+    #[test]
+    fn unknown_zone_is_skipped() {
+        let calendar = calendar(
+            "BEGIN:VTIMEZONE\nTZID:Custom\n\
+             BEGIN:STANDARD\nDTSTART:16010101T030000\nTZOFFSETFROM:+0200\nTZOFFSETTO:+0100\nEND:STANDARD\n\
+             BEGIN:DAYLIGHT\nDTSTART:16010101T020000\nTZOFFSETFROM:+0100\nTZOFFSETTO:+0200\nEND:DAYLIGHT\n\
+             END:VTIMEZONE\n\
+             BEGIN:VEVENT\nUID:a\nDTSTART;TZID=Custom:20261026T093000\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:b\nDTSTART;TZID=Nowhere Standard Time:20261026T093000\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:c\nDTSTART:20261026T093000Z\nEXDATE;TZID=Nowhere:20261026T093000\nEND:VEVENT",
+        );
+        assert!(calendar.events.is_empty());
+        assert_eq!(calendar.skipped, 3);
+    }
+}
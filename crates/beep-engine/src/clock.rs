@@ -0,0 +1,89 @@
+// 时钟抽象：引擎只通过 Clock 读取时间，测试中可以换成手动推进的时钟
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+
+// 毫秒
+pub type Millis = u64;
+
+pub trait Clock {
+    // 单调时间（毫秒），起点任意，只用于计算间隔和截止时刻
+    fn now_ms(&self) -> Millis;
+    // 墙上时间（Unix 毫秒），只用于对外展示截止时刻
+    fn unix_ms(&self) -> i64;
+}
+
+// 基于 std::time::Instant 的系统时钟，WebAssembly 中 Instant 不可用，需要由宿主提供时钟
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct SystemClock {
+    origin: std::time::Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn now_ms(&self) -> Millis {
+        self.origin.elapsed().as_millis() as Millis
+    }
+
+    fn unix_ms(&self) -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+}
+
+// 手动推进的时钟，克隆出的句柄共享同一个时间，用于模拟长时间运行
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+    unix_origin: Arc<AtomicI64>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 指定单调时间为 0 时对应的墙上时间
+    pub fn starting_at_unix(unix_ms: i64) -> Self {
+        let clock = Self::new();
+        clock.unix_origin.store(unix_ms, Ordering::SeqCst);
+        clock
+    }
+
+    pub fn set(&self, now: Millis) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, delta: Millis) {
+        self.now.fetch_add(delta, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> Millis {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    fn unix_ms(&self) -> i64 {
+        self.unix_origin.load(Ordering::SeqCst) + self.now_ms() as i64
+    }
+}
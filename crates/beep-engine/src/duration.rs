@@ -0,0 +1,186 @@
+// 时长输入的解析，前端输入框和后端导入的预设文件共用
+// 支持的写法：
+//   90、90s、2.5m、1h30m、1 小时 30 分    数字加单位，可以组合，没有单位时按秒计
+//   25:00、1:02:03                        分:秒 或 时:分:秒
+//   until 17:30、到 17:30                 到下一个本地时间 17:30 为止
+
+use chrono::{DateTime, Days, NaiveTime, TimeZone};
+
+use crate::alarm;
+
+const MAX_SECONDS: f64 = u32::MAX as f64;
+
+// 解析时长，返回秒数；now 只在“until”写法中用来计算距离目标时间还有多久
+pub fn parse_duration<Tz: TimeZone>(text: &str, now: &DateTime<Tz>) -> Result<u32, String> {
+    let input = text.trim().to_lowercase();
+    if input.is_empty() {
+        return Err("请输入时长".to_string());
+    }
+    let error = || format!("无法识别的时长：“{}”，可以写成 90s、2.5m、1h30m、25:00 或 until 17:30", text.trim());
+
+    if let Some(time) = input.strip_prefix("until").or_else(|| input.strip_prefix("到")) {
+        return seconds_until(time.trim(), now).ok_or_else(error);
+    }
+    if input.contains(':') {
+        return parse_clock(&input).ok_or_else(error);
+    }
+    parse_units(&input).ok_or_else(error)
+}
+
+// 时长写成 分:秒 或 时:分:秒，解析时可以原样读回
+pub fn format_clock(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+// 分:秒 或 时:分:秒，除第一段外每段都必须小于 60
+fn parse_clock(input: &str) -> Option<u32> {
+    let parts: Vec<u64> = input.split(':').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+    if !(2..=3).contains(&parts.len()) || parts[1..].iter().any(|part| *part >= 60) {
+        return None;
+    }
+    let seconds = parts.iter().fold(0u64, |total, part| total.saturating_mul(60).saturating_add(*part));
+    u32::try_from(seconds).ok()
+}
+
+// 单位对应的秒数
+fn unit_seconds(unit: &str) -> Option<f64> {
+    match unit {
+        "" | "s" | "sec" | "secs" | "second" | "seconds" | "秒" | "秒钟" => Some(1.0),
+        "m" | "min" | "mins" | "minute" | "minutes" | "分" | "分钟" => Some(60.0),
+        "h" | "hr" | "hrs" | "hour" | "hours" | "时" | "小时" => Some(3600.0),
+        _ => None,
+    }
+}
+
+// 一串“数字 + 单位”，例如 1h30m、2.5m、1 小时 30 分
+fn parse_units(input: &str) -> Option<u32> {
+    let mut total = 0.0;
+    let mut rest = input.trim_start();
+    let mut parts = 0;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = rest[number_end..].trim_start();
+        let unit_end = rest.find(|c: char| c.is_ascii_digit() || c.is_whitespace()).unwrap_or(rest.len());
+        let unit = &rest[..unit_end];
+        rest = rest[unit_end..].trim_start();
+        parts += 1;
+        // 只有单独一个数字时才可以省略单位，“1h30” 这样的写法有歧义
+        if unit.is_empty() && (parts > 1 || !rest.is_empty()) {
+            return None;
+        }
+        total += number * unit_seconds(unit)?;
+    }
+    let total = total.round();
+    (total.is_finite() && total <= MAX_SECONDS).then_some(total as u32)
+}
+
+// 距离下一个本地时间 HH:MM 的秒数，今天已经过了就算到明天
+fn seconds_until<Tz: TimeZone>(time: &str, now: &DateTime<Tz>) -> Option<u32> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    let tz = now.timezone();
+    let today = now.date_naive();
+    [today, today.checked_add_days(Days::new(1))?]
+        .into_iter()
+        .filter_map(|date| alarm::resolve(&tz, date.and_time(time)))
+        .find(|target| target > now)
+        .and_then(|target| {
+            let ms = target.timestamp_millis() - now.timestamp_millis();
+            u32::try_from((ms + 999) / 1000).ok()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn parse(text: &str) -> Result<u32, String> {
+        parse_duration(text, &Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn bare_numbers_are_seconds() {
+        assert_eq!(parse("90"), Ok(90));
+        assert_eq!(parse("0"), Ok(0));
+        assert_eq!(parse("2.5"), Ok(3));
+    }
+
+    #[test]
+    fn units_and_compounds() {
+        assert_eq!(parse("90s"), Ok(90));
+        assert_eq!(parse("2.5m"), Ok(150));
+        assert_eq!(parse("1h30m"), Ok(5400));
+        assert_eq!(parse("1h 30m 15s"), Ok(5415));
+        assert_eq!(parse("2 hours 5 minutes"), Ok(7500));
+        assert_eq!(parse("1 小时 30 分"), Ok(5400));
+        assert_eq!(parse("1H30M"), Ok(5400));
+        assert_eq!(parse("45秒钟"), Ok(45));
+    }
+
+    #[test]
+    fn whitespace_is_ignored() {
+        assert_eq!(parse("  25m\t"), Ok(1500));
+        assert_eq!(parse(" 1h   30m "), Ok(5400));
+        assert_eq!(parse(" 25 : 00 "), Ok(1500));
+    }
+
+    #[test]
+    fn clock_notation() {
+        assert_eq!(parse("25:00"), Ok(1500));
+        assert_eq!(parse("0:05"), Ok(5));
+        assert_eq!(parse("1:02:03"), Ok(3723));
+        assert_eq!(parse("100:00"), Ok(6000));
+        assert!(parse("1:60").is_err());
+        assert!(parse("1:00:60").is_err());
+        assert!(parse("1:2:3:4").is_err());
+        assert!(parse("1:").is_err());
+        assert!(parse(":30").is_err());
+    }
+
+    #[test]
+    fn format_clock_round_trips() {
+        for seconds in [0, 5, 59, 60, 1500, 3599, 3600, 3723, 86_400] {
+            assert_eq!(parse(&format_clock(seconds)), Ok(seconds));
+        }
+        assert_eq!(format_clock(3723), "1:02:03");
+        assert_eq!(format_clock(1500), "25:00");
+    }
+
+    #[test]
+    fn until_counts_to_next_local_time() {
+        assert_eq!(parse("until 17:30"), Ok(5 * 3600 + 30 * 60));
+        assert_eq!(parse("到 13:00"), Ok(3600));
+        // 已经过去的时刻算到明天
+        assert_eq!(parse("until 09:00"), Ok(21 * 3600));
+        assert_eq!(parse("until 12:00"), Ok(24 * 3600));
+        assert!(parse("until 25:00").is_err());
+        assert!(parse("until").is_err());
+    }
+
+    #[test]
+    fn overflow_is_rejected() {
+        assert_eq!(parse("4294967295"), Ok(u32::MAX));
+        assert!(parse("4294967296").is_err());
+        assert!(parse("1193047h").is_err());
+        assert!(parse("99999999999999999999h").is_err());
+        assert!(parse("4294967295:00").is_err());
+        assert!(parse("99999999999999999999:00:00").is_err());
+    }
+
+    #[test]
+    fn invalid_input() {
+        for text in ["", "   ", "abc", "5x", "1h30", "30 5m", "-5m", "1.2.3m", "m", "5 m m", "1h-30m"] {
+            let error = parse(text).unwrap_err();
+            assert!(!error.is_empty(), "{:?}", text);
+        }
+        assert_eq!(parse(" ").unwrap_err(), "请输入时长");
+        assert!(parse("5x").unwrap_err().contains("“5x”"));
+    }
+}
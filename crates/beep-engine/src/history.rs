@@ -0,0 +1,333 @@
+// 计时历史的统计：按本地日期和按周（周一开始）汇总实际计时时长与完成率，以及导出为 CSV 和 iCalendar
+// 记录本身由宿主保存，这里只做计算，界面和后端共用
+
+use std::fmt::Display;
+
+use beep_protocol::{Session, SessionFormat, SessionOutcome, TimerKind};
+use chrono::{DateTime, Datelike, Days, NaiveDate, SecondsFormat, TimeZone, Utc};
+
+use crate::duration::format_clock;
+
+// 一天或一周的汇总
+#[derive(Clone, Debug, PartialEq)]
+pub struct Period {
+    // 这一天，或这一周的周一
+    pub start: NaiveDate,
+    pub actual_ms: u64,
+    pub sessions: u32,
+    pub completed: u32,
+}
+
+impl Period {
+    fn new(start: NaiveDate) -> Self {
+        Self { start, actual_ms: 0, sessions: 0, completed: 0 }
+    }
+
+    fn add(&mut self, session: &Session) {
+        self.actual_ms += session.actual_ms;
+        self.sessions += 1;
+        if session.outcome == SessionOutcome::Completed {
+            self.completed += 1;
+        }
+    }
+
+    // 完成的次数占全部记录的比例，没有记录时为空
+    pub fn completion_rate(&self) -> Option<f64> {
+        (self.sessions > 0).then(|| f64::from(self.completed) / f64::from(self.sessions))
+    }
+}
+
+// 记录开始时的本地日期
+fn local_date<Tz: TimeZone>(unix_ms: i64, tz: &Tz) -> Option<NaiveDate> {
+    DateTime::from_timestamp_millis(unix_ms).map(|time| time.with_timezone(tz).date_naive())
+}
+
+// 把记录按开始日期归入各个长度为 length 天的时间段，不在任何时间段内的记录忽略
+fn totals<Tz: TimeZone>(sessions: &[Session], tz: &Tz, starts: Vec<NaiveDate>, length: u64) -> Vec<Period> {
+    let mut periods: Vec<Period> = starts.into_iter().map(Period::new).collect();
+    for session in sessions {
+        let Some(date) = local_date(session.started_unix_ms, tz) else {
+            continue;
+        };
+        let period = periods.iter_mut().find(|period| {
+            period.start <= date && period.start.checked_add_days(Days::new(length)).is_none_or(|end| date < end)
+        });
+        if let Some(period) = period {
+            period.add(session);
+        }
+    }
+    periods
+}
+
+// 截至 today 的最近 days 天，较早的在前
+pub fn daily<Tz: TimeZone>(sessions: &[Session], tz: &Tz, today: NaiveDate, days: u64) -> Vec<Period> {
+    let starts = (0..days).rev().filter_map(|n| today.checked_sub_days(Days::new(n))).collect();
+    totals(sessions, tz, starts, 1)
+}
+
+// 包括本周在内的最近 weeks 周，较早的在前
+pub fn weekly<Tz: TimeZone>(sessions: &[Session], tz: &Tz, today: NaiveDate, weeks: u64) -> Vec<Period> {
+    let Some(monday) = today.checked_sub_days(Days::new(u64::from(today.weekday().num_days_from_monday()))) else {
+        return Vec::new();
+    };
+    let starts = (0..weeks).rev().filter_map(|n| monday.checked_sub_days(Days::new(n * 7))).collect();
+    totals(sessions, tz, starts, 7)
+}
+
+// 在 [from_unix_ms, to_unix_ms) 内开始的记录
+pub fn in_range(sessions: Vec<Session>, from_unix_ms: i64, to_unix_ms: i64) -> Vec<Session> {
+    sessions
+        .into_iter()
+        .filter(|session| (from_unix_ms..to_unix_ms).contains(&session.started_unix_ms))
+        .collect()
+}
+
+// 按格式导出记录；CSV 的时间使用 tz 时区，iCalendar 一律使用 UTC
+pub fn export<Tz: TimeZone>(sessions: &[Session], format: SessionFormat, tz: &Tz, now_unix_ms: i64) -> String
+where
+    Tz::Offset: Display,
+{
+    match format {
+        SessionFormat::Csv => to_csv(sessions, tz),
+        SessionFormat::Ics => to_ics(sessions, now_unix_ms),
+    }
+}
+
+fn kind_name(kind: TimerKind) -> &'static str {
+    match kind {
+        TimerKind::Countdown => "countdown",
+        TimerKind::Stopwatch => "stopwatch",
+        TimerKind::Pomodoro => "pomodoro",
+        TimerKind::Interval => "interval",
+        TimerKind::Sequence => "sequence",
+    }
+}
+
+fn outcome_name(outcome: SessionOutcome) -> &'static str {
+    match outcome {
+        SessionOutcome::Completed => "completed",
+        SessionOutcome::Cancelled => "cancelled",
+        SessionOutcome::Reset => "reset",
+    }
+}
+
+// CSV 字段：含逗号、引号或换行时加引号，引号写两遍
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn iso_time<Tz: TimeZone>(unix_ms: i64, tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    DateTime::from_timestamp_millis(unix_ms)
+        .map(|time| time.with_timezone(tz).to_rfc3339_opts(SecondsFormat::Secs, false))
+        .unwrap_or_default()
+}
+
+// 列的顺序固定，供电子表格和工时系统导入；时长以秒为单位，保留到毫秒
+pub fn to_csv<Tz: TimeZone>(sessions: &[Session], tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    let mut csv = String::from("timer_id,label,kind,outcome,planned_seconds,actual_seconds,started_at,ended_at\n");
+    for session in sessions {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}.{:03},{},{}\n",
+            csv_field(&session.id),
+            csv_field(&session.label),
+            kind_name(session.kind),
+            outcome_name(session.outcome),
+            session.planned_seconds,
+            session.actual_ms / 1000,
+            session.actual_ms % 1000,
+            iso_time(session.started_unix_ms, tz),
+            iso_time(session.ended_unix_ms, tz),
+        ));
+    }
+    csv
+}
+
+// iCalendar 的 UTC 时间，例如 20240501T013000Z
+fn ics_time(unix_ms: i64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(unix_ms)
+        .map(|time| time.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+// iCalendar 文本值中的反斜杠、分号、逗号和换行需要转义
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+// 按 RFC 5545 折行：每行不超过 75 字节，续行以一个空格开头，不拆开多字节字符
+fn ics_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn outcome_text(outcome: SessionOutcome) -> &'static str {
+    match outcome {
+        SessionOutcome::Completed => "完成",
+        SessionOutcome::Cancelled => "取消",
+        SessionOutcome::Reset => "重置",
+    }
+}
+
+// 每次计时一个 VEVENT，UID 由开始时间和计时器 id 组成，重复导入同一段记录时日历可以去重
+pub fn to_ics(sessions: &[Session], now_unix_ms: i64) -> String {
+    let mut ics = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//beep//timer history//ZH", "CALSCALE:GREGORIAN"] {
+        ics_line(&mut ics, line);
+    }
+    let stamp = ics_time(now_unix_ms);
+    for session in sessions {
+        let description = format!(
+            "{}：计划 {}，实际 {}",
+            outcome_text(session.outcome),
+            format_clock(session.planned_seconds),
+            format_clock((session.actual_ms / 1000).min(u64::from(u32::MAX)) as u32),
+        );
+        ics_line(&mut ics, "BEGIN:VEVENT");
+        ics_line(&mut ics, &format!("UID:{}-{}@beep", session.started_unix_ms, ics_text(&session.id)));
+        ics_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        ics_line(&mut ics, &format!("DTSTART:{}", ics_time(session.started_unix_ms)));
+        ics_line(&mut ics, &format!("DTEND:{}", ics_time(session.ended_unix_ms)));
+        ics_line(&mut ics, &format!("SUMMARY:{}", ics_text(&session.label)));
+        ics_line(&mut ics, &format!("DESCRIPTION:{}", ics_text(&description)));
+        ics_line(&mut ics, "END:VEVENT");
+    }
+    ics_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    // 2026-10-17 09:00:00 UTC
+    const START_UNIX_MS: i64 = 1_792_227_600_000;
+
+    fn session(id: &str, label: &str, outcome: SessionOutcome) -> Session {
+        Session {
+            id: id.to_string(),
+            label: label.to_string(),
+            kind: TimerKind::Pomodoro,
+            planned_seconds: 1500,
+            actual_ms: 1_500_250,
+            started_unix_ms: START_UNIX_MS,
+            ended_unix_ms: START_UNIX_MS + 1_500_250,
+            outcome,
+        }
+    }
+
+    // 把 CSV 的一行拆成字段，按 RFC 4180 处理引号
+    fn csv_fields(line: &str) -> Vec<String> {
+        let mut fields = vec![String::new()];
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    fields.last_mut().unwrap().push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(String::new()),
+                c => fields.last_mut().unwrap().push(c),
+            }
+        }
+        fields
+    }
+
+    // 去掉折行，得到各个内容行
+    fn unfold(ics: &str) -> Vec<String> {
+        ics.replace("\r\n ", "").split("\r\n").filter(|line| !line.is_empty()).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn csv_columns_in_order() {
+        let csv = to_csv(&[session("work", "写报告", SessionOutcome::Completed)], &Berlin);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "timer_id,label,kind,outcome,planned_seconds,actual_seconds,started_at,ended_at",
+                "work,写报告,pomodoro,completed,1500,1500.250,2026-10-17T11:00:00+02:00,2026-10-17T11:25:00+02:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_times_use_given_zone() {
+        let csv = to_csv(&[session("a", "b", SessionOutcome::Reset)], &Utc);
+        assert!(csv.contains(",reset,1500,1500.250,2026-10-17T09:00:00+00:00,2026-10-17T09:25:00+00:00\n"));
+    }
+
+    #[test]
+    fn csv_escapes_quotes_commas_and_newlines() {
+        let label = "会议, \"周报\"\n第二行";
+        let csv = to_csv(&[session("a,b", label, SessionOutcome::Cancelled)], &Utc);
+        assert!(csv.contains("\"a,b\",\"会议, \"\"周报\"\"\n第二行\",pomodoro,cancelled"));
+        // 去掉表头后按引号规则拆开，字段与原文一致
+        let record = csv.split_once('\n').unwrap().1.trim_end_matches('\n');
+        let fields = csv_fields(record);
+        assert_eq!(fields.len(), 8);
+        assert_eq!(fields[0], "a,b");
+        assert_eq!(fields[1], label);
+        assert_eq!(fields[3], "cancelled");
+    }
+
+    #[test]
+    fn ics_lines_are_folded_at_75_octets() {
+        let label = "很长的计时名称".repeat(10) + &"x".repeat(50);
+        let ics = to_ics(&[session("a", &label, SessionOutcome::Completed)], START_UNIX_MS);
+        assert!(ics.ends_with("\r\n"));
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75, "{} octets: {:?}", line.len(), line);
+        }
+        assert!(ics.contains("\r\n "));
+        assert!(unfold(&ics).contains(&format!("SUMMARY:{}", label)));
+    }
+
+    #[test]
+    fn ics_escapes_text() {
+        let label = "a;b,c\\d\ne\r\nf";
+        let ics = to_ics(&[session("x;y", label, SessionOutcome::Completed)], START_UNIX_MS);
+        let lines = unfold(&ics);
+        assert!(lines.contains(&"SUMMARY:a\\;b\\,c\\\\d\\ne\\nf".to_string()), "{:?}", lines);
+        assert!(lines.contains(&format!("UID:{}-x\\;y@beep", START_UNIX_MS)));
+        assert!(lines.contains(&"DESCRIPTION:完成：计划 25:00，实际 25:00".to_string()));
+        // 文本中的回车也被转义，输出中的回车只出现在行尾
+        assert_eq!(ics.matches('\r').count(), ics.matches("\r\n").count());
+    }
+
+    #[test]
+    fn ics_times_are_utc() {
+        let ics = to_ics(&[session("a", "b", SessionOutcome::Completed)], START_UNIX_MS + 3_600_000);
+        let lines = unfold(&ics);
+        assert_eq!(lines.first().map(String::as_str), Some("BEGIN:VCALENDAR"));
+        assert_eq!(lines.last().map(String::as_str), Some("END:VCALENDAR"));
+        for expected in ["DTSTAMP:20261017T100000Z", "DTSTART:20261017T090000Z", "DTEND:20261017T092500Z"] {
+            assert!(lines.iter().any(|line| line == expected), "{:?}", lines);
+        }
+    }
+}
@@ -0,0 +1,1312 @@
+// 计时引擎（倒计时、秒表与闹钟）：不依赖 Leptos、Tauri 或任何线程模型
+// 宿主负责在 next_wakeup() 给出的时刻调用 poll()，引擎通过回调报告生命周期事件
+
+pub mod alarm;
+pub mod calendar;
+mod clock;
+pub mod duration;
+pub mod history;
+pub mod plan;
+pub mod preset;
+pub mod program;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use beep_protocol::{
+    Alarm, AlarmRepeat, FinishOptions, IntervalConfig, Lap, OpenSession, Plan, PomodoroConfig, Segment, SequenceConfig,
+    Session, SessionOutcome, TimerEvent, TimerKind, TimerPhase, TimerSnapshot, CALENDAR_TIMER_PREFIX,
+};
+
+use calendar::CalendarEvent;
+use chrono::{DateTime, Local};
+
+pub use clock::{Clock, ManualClock, Millis};
+#[cfg(not(target_arch = "wasm32"))]
+pub use clock::SystemClock;
+
+const SECOND_MS: Millis = 1000;
+
+// 等待闹钟时最长的唤醒间隔：系统休眠或用户修改系统时间、时区后，最迟这么久就会重新核对
+const ALARM_RECHECK_MS: Millis = 30 * SECOND_MS;
+
+// 只为这段时间内到点的日历提醒创建倒计时，免得计时器列表里堆满还很遥远的会议
+const CALENDAR_HORIZON_MS: i64 = 24 * 3600 * 1000;
+
+// 日历最长多久重新核对一次，过了视野的事件、修改系统时间或时区都在下一次核对时处理
+const CALENDAR_RECHECK_MS: Millis = 60 * SECOND_MS;
+
+// 运行中的提醒与应有的到点时刻相差超过这么多（例如修改了提前量）时重新设定
+const CALENDAR_TOLERANCE_MS: i64 = 30 * SECOND_MS as i64;
+
+// 事件回调
+type Listener = Box<dyn FnMut(&TimerEvent) + Send>;
+
+// 单个计时器的状态
+#[derive(Default)]
+struct Timer {
+    label: String,
+    kind: TimerKind,
+    phase: TimerPhase,
+    total_seconds: u32,
+    // 倒计时未运行时的剩余时间，保留到毫秒以便暂停后精确继续
+    remaining_ms: Millis,
+    // 运行中的倒计时的截止时刻，剩余时间总是由它推算，不会累积误差
+    deadline: Option<Millis>,
+    // 秒表在本次运行之前累计的用时
+    elapsed_ms: Millis,
+    // 秒表本次开始运行的时刻，已用时间总是由它推算
+    run_start: Option<Millis>,
+    laps: Vec<Lap>,
+    plan: Option<Plan>,
+    pomodoro: Option<PomodoroConfig>,
+    interval: Option<IntervalConfig>,
+    sequence: Option<SequenceConfig>,
+    finish: FinishOptions,
+    // 超时阶段在 overtime_since 之前累计的超时
+    overtime_ms: Millis,
+    // 超时计时的起点，超时总是由它推算
+    overtime_since: Option<Millis>,
+    // 应用关闭期间错过的截止时刻（Unix 毫秒）
+    missed_unix_ms: Option<i64>,
+    // 进行中的计时记录，从开始到完成、取消或重置
+    session: Option<OpenSession>,
+    // 下一次 tick 的计划时刻：倒计时为截止时刻前的下一个整秒边界，秒表为已用时间的下一个整秒
+    next_tick: Option<Millis>,
+    // 最近一次 tick 相对计划时刻的延迟（毫秒），用于诊断
+    last_drift_ms: i64,
+}
+
+impl Timer {
+    fn is_running(&self) -> bool {
+        self.phase == TimerPhase::Running
+    }
+
+    // 切换阶段，非法转换返回错误
+    fn transition(&mut self, next: TimerPhase) -> Result<(), String> {
+        if !self.phase.can_transition_to(next) {
+            return Err(format!("非法的状态转换: {:?} -> {:?}", self.phase, next));
+        }
+        self.phase = next;
+        Ok(())
+    }
+
+    fn total_ms(&self) -> Millis {
+        Millis::from(self.total_seconds) * SECOND_MS
+    }
+
+    fn remaining_at(&self, now: Millis) -> Millis {
+        if self.kind.counts_up() {
+            // 秒表距离目标时间还剩多少，没有目标时为 0
+            return self.total_ms().saturating_sub(self.elapsed_at(now));
+        }
+        match self.deadline {
+            Some(deadline) => deadline.saturating_sub(now),
+            None => self.remaining_ms,
+        }
+    }
+
+    fn elapsed_at(&self, now: Millis) -> Millis {
+        if !self.kind.counts_up() {
+            return self.total_ms().saturating_sub(self.remaining_at(now));
+        }
+        match self.run_start {
+            Some(run_start) => self.elapsed_ms + now.saturating_sub(run_start),
+            None => self.elapsed_ms,
+        }
+    }
+
+    // 到点后超出的时间，未超时为 0
+    fn overtime_at(&self, now: Millis) -> Millis {
+        match self.overtime_since {
+            Some(since) => self.overtime_ms + now.saturating_sub(since),
+            None => self.overtime_ms,
+        }
+    }
+
+    // 开始运行：倒计时设定截止时刻，秒表记下起点，并安排第一次 tick
+    fn arm(&mut self, now: Millis) {
+        if self.kind.counts_up() {
+            self.run_start = Some(now);
+            self.next_tick = Some(next_tick_after(self.elapsed_ms, now));
+        } else {
+            let deadline = now + self.remaining_ms;
+            self.deadline = Some(deadline);
+            self.next_tick = Some(next_tick_before(deadline, now));
+        }
+        self.last_drift_ms = 0;
+    }
+
+    // 停止计时，记下精确的剩余时间或已用时间
+    fn disarm(&mut self, now: Millis) {
+        if let Some(deadline) = self.deadline.take() {
+            self.remaining_ms = deadline.saturating_sub(now);
+        }
+        if self.run_start.is_some() {
+            self.elapsed_ms = self.elapsed_at(now);
+            self.run_start = None;
+        }
+        if self.overtime_since.is_some() {
+            self.overtime_ms = self.overtime_at(now);
+            self.overtime_since = None;
+        }
+        self.next_tick = None;
+    }
+
+    // 回到起点：倒计时恢复总时间，秒表清零并清空圈数
+    fn rewind(&mut self) {
+        self.deadline = None;
+        self.run_start = None;
+        self.next_tick = None;
+        self.remaining_ms = self.total_ms();
+        self.elapsed_ms = 0;
+        self.overtime_ms = 0;
+        self.overtime_since = None;
+        self.missed_unix_ms = None;
+        self.laps.clear();
+    }
+
+    // 开始一次新的计时记录，分段计划中以当前分段命名
+    fn begin_session(&mut self, unix_now: i64, planned_seconds: u32) {
+        let label = match self.plan.as_ref().and_then(|plan| plan.current()) {
+            Some(segment) => segment.label.clone(),
+            None => self.label.clone(),
+        };
+        self.session = Some(OpenSession {
+            label,
+            planned_seconds,
+            started_unix_ms: unix_now,
+            paused_ms: 0,
+            paused_since_unix_ms: None,
+        });
+    }
+
+    fn pause_session(&mut self, unix_now: i64) {
+        if let Some(session) = self.session.as_mut() {
+            session.paused_since_unix_ms.get_or_insert(unix_now);
+        }
+    }
+
+    fn resume_session(&mut self, unix_now: i64) {
+        if let Some(session) = self.session.as_mut() {
+            if let Some(since) = session.paused_since_unix_ms.take() {
+                session.paused_ms += (unix_now - since).max(0) as u64;
+            }
+        }
+    }
+
+    // 结束当前的计时记录，没有进行中的记录时返回空
+    fn end_session(&mut self, id: &str, ended_unix_ms: i64, outcome: SessionOutcome) -> Option<Session> {
+        let session = self.session.take()?;
+        let paused_ms = session.paused_ms
+            + session.paused_since_unix_ms.map_or(0, |since| (ended_unix_ms - since).max(0) as u64);
+        Some(Session {
+            id: id.to_string(),
+            label: session.label,
+            kind: self.kind,
+            planned_seconds: session.planned_seconds,
+            actual_ms: ((ended_unix_ms - session.started_unix_ms).max(0) as u64).saturating_sub(paused_ms),
+            started_unix_ms: session.started_unix_ms,
+            ended_unix_ms,
+            outcome,
+        })
+    }
+
+    // 计时被中途打断（删除、切换方式、重新开始）时的结果，超时中的倒计时已经到过点，算作完成
+    fn interrupted_outcome(&self) -> SessionOutcome {
+        if self.phase == TimerPhase::Overtime {
+            SessionOutcome::Completed
+        } else {
+            SessionOutcome::Cancelled
+        }
+    }
+
+    // 分段计划回到第一段
+    fn restart_plan(&mut self) {
+        if let Some(plan) = self.plan.as_mut() {
+            plan.index = 0;
+            plan.completed_sessions = 0;
+            if let Some(first) = plan.current() {
+                self.total_seconds = first.seconds;
+            }
+        }
+    }
+
+    // 当前段结束，切换到下一段，返回刚结束的段和接下来的段名
+    // 没有计划或计划已经走完时返回空，此时计时器按普通倒计时结束
+    fn advance_plan(&mut self) -> Option<(Segment, String)> {
+        let plan = self.plan.as_mut()?;
+        let ended = plan.current()?.clone();
+        if ended.session {
+            plan.completed_sessions += 1;
+        }
+        let next = plan.upcoming()?.clone();
+        plan.index = (plan.index + 1) % plan.segments.len() as u32;
+        self.total_seconds = next.seconds;
+        self.rewind();
+        Some((ended, next.label))
+    }
+}
+
+// 剩余整秒数，向上取整，这样只有真正到点时才显示 0
+fn ceil_seconds(ms: Millis) -> u32 {
+    ms.div_ceil(SECOND_MS).min(u32::MAX as Millis) as u32
+}
+
+// 截止时刻前、当前时刻之后的下一个整秒边界
+fn next_tick_before(deadline: Millis, now: Millis) -> Millis {
+    let secs_after_tick = ceil_seconds(deadline.saturating_sub(now)).saturating_sub(1);
+    deadline - Millis::from(secs_after_tick) * SECOND_MS
+}
+
+// 已用时间到达下一个整秒的时刻
+fn next_tick_after(elapsed: Millis, now: Millis) -> Millis {
+    now + SECOND_MS - elapsed % SECOND_MS
+}
+
+// 已用整秒数，向下取整
+fn floor_seconds(ms: Millis) -> u32 {
+    (ms / SECOND_MS).min(u32::MAX as Millis) as u32
+}
+
+// Unix 毫秒对应的本地时间
+fn local_time(unix_ms: i64) -> Option<DateTime<Local>> {
+    DateTime::from_timestamp_millis(unix_ms).map(|time| time.with_timezone(&Local))
+}
+
+// 按 id 管理多个计时器和闹钟
+pub struct TimerEngine<C: Clock> {
+    clock: C,
+    timers: BTreeMap<String, Timer>,
+    next_id: u64,
+    alarms: BTreeMap<String, Alarm>,
+    // 闹钟每次变化（包括响铃后计算出新的响铃时刻）都会加一，宿主据此判断是否需要保存
+    alarms_revision: u64,
+    // 计时器每次发生需要保存的变化（不包括 tick）都会加一
+    timers_revision: u64,
+    // 已经结束、还没有被宿主取走的计时记录
+    sessions: Vec<Session>,
+    // 导入的日历，按来源（通常是文件路径）区分：提前多少分钟提醒，以及其中的事件
+    calendars: BTreeMap<String, (u32, Vec<CalendarEvent>)>,
+    // 用户删除的日历提醒，这一次事件开始之前不再重新创建
+    dismissed_reminders: BTreeSet<String>,
+    // 上一次核对日历的时刻，没有日历时为空
+    calendar_synced: Option<Millis>,
+    listener: Option<Listener>,
+}
+
+impl<C: Clock> TimerEngine<C> {
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            timers: BTreeMap::new(),
+            next_id: 0,
+            alarms: BTreeMap::new(),
+            alarms_revision: 0,
+            timers_revision: 0,
+            sessions: Vec::new(),
+            calendars: BTreeMap::new(),
+            dismissed_reminders: BTreeSet::new(),
+            calendar_synced: None,
+            listener: None,
+        }
+    }
+
+    // 注册事件回调，之后的所有生命周期事件都会交给它
+    pub fn on_event(&mut self, listener: impl FnMut(&TimerEvent) + Send + 'static) {
+        self.listener = Some(Box::new(listener));
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    fn emit(&mut self, event: TimerEvent) {
+        // 到点、切换分段等由 poll() 引起的变化都会发出事件，据此记录计时器的变化
+        if !matches!(event, TimerEvent::Tick { .. } | TimerEvent::AlarmFired { .. }) {
+            self.timers_revision += 1;
+        }
+        if let Some(listener) = self.listener.as_mut() {
+            listener(&event);
+        }
+    }
+
+    // 取出计时器准备修改，同时记为有变化
+    fn get_mut(&mut self, id: &str) -> Result<&mut Timer, String> {
+        self.timers_revision += 1;
+        self.timers.get_mut(id).ok_or_else(|| format!("计时器不存在: {}", id))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.timers.contains_key(id)
+    }
+
+    // 计时器的完整快照
+    pub fn snapshot(&self, id: &str) -> Result<TimerSnapshot, String> {
+        let timer = self.timers.get(id).ok_or_else(|| format!("计时器不存在: {}", id))?;
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let remaining_ms = timer.remaining_at(now);
+        let elapsed_ms = timer.elapsed_at(now);
+        let overtime_ms = timer.overtime_at(now);
+        // 超时中的计时器给出已经过去的截止时刻，重新加载后据此继续累计超时
+        let deadline_unix_ms = match (timer.deadline, timer.overtime_since) {
+            (Some(deadline), _) => Some(unix_now + deadline as i64 - now as i64),
+            (None, Some(_)) => Some(unix_now - overtime_ms as i64),
+            (None, None) => None,
+        };
+        Ok(TimerSnapshot {
+            id: id.to_string(),
+            label: timer.label.clone(),
+            kind: timer.kind,
+            phase: timer.phase,
+            remaining_seconds: ceil_seconds(remaining_ms),
+            remaining_ms,
+            elapsed_ms,
+            total_seconds: timer.total_seconds,
+            deadline_unix_ms,
+            started_unix_ms: timer.run_start.map(|_| unix_now - elapsed_ms as i64),
+            laps: timer.laps.clone(),
+            plan: timer.plan.clone(),
+            pomodoro: timer.pomodoro.clone(),
+            interval: timer.interval.clone(),
+            sequence: timer.sequence.clone(),
+            finish: timer.finish.clone(),
+            overtime_ms,
+            missed_deadline_unix_ms: timer.missed_unix_ms,
+            session: timer.session.clone(),
+            last_drift_ms: timer.last_drift_ms,
+        })
+    }
+
+    pub fn list(&self) -> Vec<TimerSnapshot> {
+        self.timers.keys().filter_map(|id| self.snapshot(id).ok()).collect()
+    }
+
+    pub fn timers_revision(&self) -> u64 {
+        self.timers_revision
+    }
+
+    // 取走已经结束的计时记录，由宿主追加到历史中
+    pub fn take_sessions(&mut self) -> Vec<Session> {
+        std::mem::take(&mut self.sessions)
+    }
+
+    // 创建计时器，未指定 id 时自动分配
+    pub fn create(
+        &mut self,
+        id: Option<String>,
+        label: Option<String>,
+        kind: TimerKind,
+        seconds: u32,
+    ) -> Result<TimerSnapshot, String> {
+        let id = match id {
+            Some(id) => {
+                if self.timers.contains_key(&id) {
+                    return Err(format!("计时器已存在: {}", id));
+                }
+                id
+            }
+            None => loop {
+                self.next_id += 1;
+                let id = format!("timer-{}", self.next_id);
+                if !self.timers.contains_key(&id) {
+                    break id;
+                }
+            },
+        };
+        self.timers_revision += 1;
+        self.timers.insert(
+            id.clone(),
+            Timer {
+                label: label.unwrap_or_else(|| id.clone()),
+                kind,
+                total_seconds: seconds,
+                remaining_ms: Millis::from(seconds) * SECOND_MS,
+                ..Timer::default()
+            },
+        );
+        self.snapshot(&id)
+    }
+
+    // 从快照恢复计时器（例如页面重新加载或应用重新启动后），已存在的同名计时器会被覆盖
+    // 运行中的计时器按墙上截止时刻重新推算剩余时间
+    // 关闭期间已经到点的倒计时不再补发结束事件，而是记为错过并发出 Missed；分段计划停在当时的分段
+    pub fn restore(&mut self, snapshot: TimerSnapshot) {
+        let now = self.clock.now_ms();
+        let mut timer = Timer {
+            label: snapshot.label.clone(),
+            kind: snapshot.kind,
+            phase: snapshot.phase,
+            total_seconds: snapshot.total_seconds,
+            remaining_ms: snapshot.remaining_ms,
+            elapsed_ms: snapshot.elapsed_ms,
+            laps: snapshot.laps,
+            plan: snapshot.plan,
+            pomodoro: snapshot.pomodoro,
+            interval: snapshot.interval,
+            sequence: snapshot.sequence,
+            finish: snapshot.finish,
+            overtime_ms: snapshot.overtime_ms,
+            missed_unix_ms: snapshot.missed_deadline_unix_ms,
+            session: snapshot.session,
+            ..Timer::default()
+        };
+        let unix_now = self.clock.unix_ms();
+        let missed = snapshot
+            .deadline_unix_ms
+            .filter(|deadline| timer.is_running() && !timer.kind.counts_up() && *deadline < unix_now);
+        if let Some(deadline_unix_ms) = missed {
+            timer.remaining_ms = 0;
+            timer.missed_unix_ms = Some(deadline_unix_ms);
+            // 开启了超时计时的从原来的截止时刻起算超时，记录留到超时结束
+            if timer.finish.overtime {
+                let _ = timer.transition(TimerPhase::Overtime);
+            } else {
+                let _ = timer.transition(TimerPhase::Finished);
+                let session = timer.end_session(&snapshot.id, deadline_unix_ms, SessionOutcome::Completed);
+                self.sessions.extend(session);
+            }
+        }
+        if timer.phase == TimerPhase::Overtime {
+            if let Some(deadline_unix_ms) = snapshot.deadline_unix_ms {
+                timer.overtime_ms = (unix_now - deadline_unix_ms).max(0) as Millis;
+            }
+            // 单调时钟可能还没走到原来的截止时刻，超时改从现在起累计
+            timer.overtime_since = Some(now);
+            timer.next_tick = Some(next_tick_after(timer.overtime_ms, now));
+        }
+        if timer.is_running() {
+            if let Some(deadline_unix_ms) = snapshot.deadline_unix_ms {
+                timer.remaining_ms = (deadline_unix_ms - unix_now).max(0) as Millis;
+            }
+            if let Some(started_unix_ms) = snapshot.started_unix_ms {
+                timer.elapsed_ms = (unix_now - started_unix_ms).max(0) as Millis;
+            }
+            timer.arm(now);
+        }
+        self.timers_revision += 1;
+        self.timers.insert(snapshot.id.clone(), timer);
+        if let Some(deadline_unix_ms) = missed {
+            let event = TimerEvent::Missed { id: snapshot.id, label: snapshot.label, deadline_unix_ms };
+            self.emit(event);
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        let unix_now = self.clock.unix_ms();
+        self.timers_revision += 1;
+        let mut timer = self.timers.remove(id).ok_or_else(|| format!("计时器不存在: {}", id))?;
+        if id.starts_with(CALENDAR_TIMER_PREFIX) {
+            self.dismissed_reminders.insert(id.to_string());
+        }
+        let outcome = timer.interrupted_outcome();
+        self.sessions.extend(timer.end_session(id, unix_now, outcome));
+        Ok(())
+    }
+
+    // 启动计时器，从头开始
+    pub fn start(&mut self, id: &str) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        if timer.is_running() {
+            return Ok(());
+        }
+        // 暂停中重新开始时原来的记录算作取消，超时中的算作完成
+        let outcome = timer.interrupted_outcome();
+        let ended = timer.end_session(id, unix_now, outcome);
+        // 走完的分段计划重新从第一段开始
+        if timer.phase == TimerPhase::Finished || timer.phase == TimerPhase::Overtime {
+            timer.restart_plan();
+        }
+        timer.transition(TimerPhase::Running)?;
+        timer.rewind();
+        timer.arm(now);
+        timer.begin_session(unix_now, timer.total_seconds);
+        let event = TimerEvent::Started {
+            id: id.to_string(),
+            remaining_seconds: timer.total_seconds,
+            total_seconds: timer.total_seconds,
+        };
+        self.sessions.extend(ended);
+        self.emit(event);
+        Ok(())
+    }
+
+    // 继续计时器，保留暂停时的剩余时间
+    pub fn resume(&mut self, id: &str) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        if timer.phase != TimerPhase::Paused {
+            return Err(format!("计时器未暂停，无法继续: {}", id));
+        }
+        timer.transition(TimerPhase::Running)?;
+        timer.arm(now);
+        timer.resume_session(unix_now);
+        let event = TimerEvent::Resumed {
+            id: id.to_string(),
+            remaining_seconds: ceil_seconds(timer.remaining_at(now)),
+        };
+        self.emit(event);
+        Ok(())
+    }
+
+    pub fn pause(&mut self, id: &str) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        timer.transition(TimerPhase::Paused)?;
+        timer.disarm(now);
+        timer.pause_session(unix_now);
+        let event = TimerEvent::Paused {
+            id: id.to_string(),
+            remaining_seconds: ceil_seconds(timer.remaining_at(now)),
+            elapsed_seconds: floor_seconds(timer.elapsed_at(now)),
+        };
+        self.emit(event);
+        Ok(())
+    }
+
+    // 重置计时器；未完成的记录算作重置，秒表停止时达到目标时间（或没有目标）算作完成
+    pub fn reset(&mut self, id: &str) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        let reached_target = timer.kind.counts_up() && timer.elapsed_at(now) >= timer.total_ms();
+        let outcome = if timer.phase == TimerPhase::Overtime || reached_target {
+            SessionOutcome::Completed
+        } else {
+            SessionOutcome::Reset
+        };
+        let ended = timer.end_session(id, unix_now, outcome);
+        timer.transition(TimerPhase::Idle)?;
+        timer.restart_plan();
+        timer.rewind();
+        let event = TimerEvent::Reset {
+            id: id.to_string(),
+            remaining_seconds: timer.total_seconds,
+        };
+        self.sessions.extend(ended);
+        self.emit(event);
+        Ok(())
+    }
+
+    // 设置总时间，运行中的倒计时从现在起按新时长倒计时，其余情况回到未开始
+    // 秒表的总时间只是目标时间，修改它不影响计时
+    // 倒计时原来的记录算作取消，运行中的按新时长开始新的记录
+    pub fn set_total(&mut self, id: &str, seconds: u32) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        timer.total_seconds = seconds;
+        if timer.kind.counts_up() {
+            return Ok(());
+        }
+        let outcome = timer.interrupted_outcome();
+        let ended = timer.end_session(id, unix_now, outcome);
+        timer.remaining_ms = timer.total_ms();
+        if timer.is_running() {
+            timer.arm(now);
+            timer.begin_session(unix_now, seconds);
+        } else {
+            timer.transition(TimerPhase::Idle)?;
+        }
+        self.sessions.extend(ended);
+        Ok(())
+    }
+
+    // 给倒计时加减时间，运行中和暂停时都不会重新开始
+    // 总时间随之增减，已经走过的部分不变，圆环进度因此保持连续；减到 0 以下按 0 计，运行中的会在下一次 poll() 时结束
+    pub fn adjust(&mut self, id: &str, delta_seconds: i64) -> Result<TimerSnapshot, String> {
+        let now = self.clock.now_ms();
+        let timer = self.get_mut(id)?;
+        if timer.kind.counts_up() {
+            return Err(format!("秒表不能调整时间: {}", id));
+        }
+        if timer.phase == TimerPhase::Finished || timer.phase == TimerPhase::Overtime {
+            return Err(format!("计时器已结束，无法调整时间: {}", id));
+        }
+        let remaining = timer.remaining_at(now) as i64;
+        let adjusted = (remaining + delta_seconds.saturating_mul(SECOND_MS as i64)).max(0) as Millis;
+        let elapsed = timer.total_ms().saturating_sub(remaining as Millis);
+        timer.total_seconds = ceil_seconds(elapsed + adjusted);
+        match timer.deadline {
+            Some(_) => {
+                let deadline = now + adjusted;
+                timer.deadline = Some(deadline);
+                timer.next_tick = Some(next_tick_before(deadline, now));
+            }
+            None => timer.remaining_ms = adjusted,
+        }
+        let event = TimerEvent::Adjusted {
+            id: id.to_string(),
+            remaining_seconds: ceil_seconds(adjusted),
+            total_seconds: timer.total_seconds,
+        };
+        self.emit(event);
+        self.snapshot(id)
+    }
+
+    // 稍后提醒：已结束或超时中的倒计时按设置的时长重新倒计时
+    // 总时间保持不变，之后重置或重新开始仍然使用原来的时长
+    pub fn snooze(&mut self, id: &str) -> Result<(), String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        if timer.phase != TimerPhase::Finished && timer.phase != TimerPhase::Overtime {
+            return Err(format!("计时器尚未结束，无法稍后提醒: {}", id));
+        }
+        let snooze_seconds = timer.finish.snooze_seconds;
+        if snooze_seconds == 0 {
+            return Err("稍后提醒的时长必须大于 0".to_string());
+        }
+        // 超时中的记录到此结束，稍后提醒另起一条记录
+        let ended = timer.end_session(id, unix_now, SessionOutcome::Completed);
+        timer.transition(TimerPhase::Running)?;
+        timer.rewind();
+        timer.remaining_ms = Millis::from(snooze_seconds) * SECOND_MS;
+        timer.arm(now);
+        timer.begin_session(unix_now, snooze_seconds);
+        let event = TimerEvent::Started {
+            id: id.to_string(),
+            remaining_seconds: snooze_seconds,
+            total_seconds: timer.total_seconds,
+        };
+        self.sessions.extend(ended);
+        self.emit(event);
+        Ok(())
+    }
+
+    // 修改到点后的处理方式；超时中关闭超时计时会立即结束
+    pub fn set_finish_options(&mut self, id: &str, options: FinishOptions) -> Result<TimerSnapshot, String> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        let mut ended = None;
+        if timer.phase == TimerPhase::Overtime && !options.overtime {
+            timer.disarm(now);
+            timer.transition(TimerPhase::Finished)?;
+            ended = timer.end_session(id, unix_now, SessionOutcome::Completed);
+        }
+        timer.finish = options;
+        self.sessions.extend(ended);
+        self.snapshot(id)
+    }
+
+    // 切换计时方式，计时器回到未开始状态；切换到番茄钟、间歇训练或顺序计时时使用默认设置
+    pub fn set_kind(&mut self, id: &str, kind: TimerKind) -> Result<TimerSnapshot, String> {
+        match kind {
+            TimerKind::Pomodoro => self.configure_pomodoro(id, PomodoroConfig::default()),
+            TimerKind::Interval => self.configure_interval(id, IntervalConfig::default()),
+            TimerKind::Sequence => self.configure_sequence(id, SequenceConfig::default()),
+            TimerKind::Countdown | TimerKind::Stopwatch => self.reconfigure(id, |timer| {
+                timer.kind = kind;
+                timer.plan = None;
+                timer.pomodoro = None;
+                timer.interval = None;
+                timer.sequence = None;
+            }),
+        }
+    }
+
+    // 设为番茄钟并从第一段专注开始
+    pub fn configure_pomodoro(&mut self, id: &str, config: PomodoroConfig) -> Result<TimerSnapshot, String> {
+        plan::validate_pomodoro(&config)?;
+        self.reconfigure(id, |timer| {
+            timer.kind = TimerKind::Pomodoro;
+            timer.plan = Some(plan::pomodoro(&config));
+            timer.pomodoro = Some(config);
+            timer.interval = None;
+            timer.sequence = None;
+        })
+    }
+
+    // 设为间歇训练并从第一段开始
+    pub fn configure_interval(&mut self, id: &str, config: IntervalConfig) -> Result<TimerSnapshot, String> {
+        let plan = plan::interval(&config)?;
+        self.reconfigure(id, |timer| {
+            timer.kind = TimerKind::Interval;
+            timer.plan = Some(plan);
+            timer.interval = Some(config);
+            timer.pomodoro = None;
+            timer.sequence = None;
+        })
+    }
+
+    // 设为顺序计时并从第一步开始
+    pub fn configure_sequence(&mut self, id: &str, config: SequenceConfig) -> Result<TimerSnapshot, String> {
+        plan::validate_sequence(&config)?;
+        self.reconfigure(id, |timer| {
+            timer.kind = TimerKind::Sequence;
+            timer.plan = Some(plan::sequence(&config));
+            timer.sequence = Some(config);
+            timer.pomodoro = None;
+            timer.interval = None;
+        })
+    }
+
+    // 停止计时器、修改设置后回到起点
+    fn reconfigure(&mut self, id: &str, configure: impl FnOnce(&mut Timer)) -> Result<TimerSnapshot, String> {
+        let unix_now = self.clock.unix_ms();
+        let timer = self.get_mut(id)?;
+        let outcome = timer.interrupted_outcome();
+        let ended = timer.end_session(id, unix_now, outcome);
+        timer.transition(TimerPhase::Idle)?;
+        configure(timer);
+        timer.restart_plan();
+        timer.rewind();
+        let event = TimerEvent::Reset {
+            id: id.to_string(),
+            remaining_seconds: timer.total_seconds,
+        };
+        self.sessions.extend(ended);
+        self.emit(event);
+        self.snapshot(id)
+    }
+
+    // 切换为倒计时并按给定时长立即开始，用于预设的一键启动
+    pub fn start_countdown(&mut self, id: &str, seconds: u32) -> Result<TimerSnapshot, String> {
+        self.set_kind(id, TimerKind::Countdown)?;
+        self.set_total(id, seconds)?;
+        self.start(id)?;
+        self.snapshot(id)
+    }
+
+    // 设为顺序计时并立即开始第一步，用于运行程序文件
+    pub fn start_sequence(&mut self, id: &str, config: SequenceConfig) -> Result<TimerSnapshot, String> {
+        self.configure_sequence(id, config)?;
+        self.start(id)?;
+        self.snapshot(id)
+    }
+
+    // 秒表记一圈，只能在运行中记录
+    pub fn lap(&mut self, id: &str) -> Result<Lap, String> {
+        let now = self.clock.now_ms();
+        let timer = self.get_mut(id)?;
+        if timer.kind != TimerKind::Stopwatch || !timer.is_running() {
+            return Err(format!("只有运行中的秒表可以记圈: {}", id));
+        }
+        let split_ms = timer.elapsed_at(now);
+        let previous_split = timer.laps.last().map_or(0, |lap| lap.split_ms);
+        let lap = Lap {
+            index: timer.laps.len() as u32 + 1,
+            lap_ms: split_ms - previous_split,
+            split_ms,
+        };
+        timer.laps.push(lap.clone());
+        let event = TimerEvent::Lap {
+            id: id.to_string(),
+            lap: lap.clone(),
+        };
+        self.emit(event);
+        Ok(lap)
+    }
+
+    pub fn alarms(&self) -> Vec<Alarm> {
+        self.alarms.values().cloned().collect()
+    }
+
+    pub fn alarms_revision(&self) -> u64 {
+        self.alarms_revision
+    }
+
+    // 新建或更新闹钟，id 为空时自动分配；返回带有下一次响铃时刻的闹钟
+    pub fn save_alarm(&mut self, mut alarm: Alarm) -> Result<Alarm, String> {
+        alarm::validate(&alarm)?;
+        if alarm.id.is_empty() {
+            alarm.id = loop {
+                self.next_id += 1;
+                let id = format!("alarm-{}", self.next_id);
+                if !self.alarms.contains_key(&id) {
+                    break id;
+                }
+            };
+        }
+        alarm.next_fire_unix_ms = self.next_fire(&alarm, self.clock.unix_ms());
+        self.alarms.insert(alarm.id.clone(), alarm.clone());
+        self.alarms_revision += 1;
+        Ok(alarm)
+    }
+
+    pub fn delete_alarm(&mut self, id: &str) -> Result<(), String> {
+        self.alarms.remove(id).ok_or_else(|| format!("闹钟不存在: {}", id))?;
+        self.alarms_revision += 1;
+        Ok(())
+    }
+
+    // 晚于 unix_ms 的下一次响铃时刻，停用的闹钟没有
+    fn next_fire(&self, alarm: &Alarm, unix_ms: i64) -> Option<i64> {
+        if !alarm.enabled {
+            return None;
+        }
+        let now = local_time(unix_ms)?;
+        alarm::next_occurrence(alarm, &now).map(|fire| fire.timestamp_millis())
+    }
+
+    // 设置某个来源的日历事件（重新读取文件时整体替换）
+    // 之后每次核对都为 24 小时内需要提醒的每一次事件保持一个倒计时，在开始前 lead_minutes 分钟到点，以事件标题命名
+    pub fn set_calendar(&mut self, source: &str, lead_minutes: u32, events: Vec<CalendarEvent>) {
+        self.calendars.insert(source.to_string(), (lead_minutes, events));
+        self.sync_calendars();
+    }
+
+    // 移除日历，由它生成的提醒随之删除
+    pub fn remove_calendar(&mut self, source: &str) {
+        if self.calendars.remove(source).is_some() {
+            self.sync_calendars();
+        }
+    }
+
+    // 按日历创建、更新和清理提醒倒计时
+    // 事件开始、被删除或改期后原来的提醒不再需要，直接删除；已经响过的提醒保留到事件开始
+    fn sync_calendars(&mut self) {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        self.calendar_synced = (!self.calendars.is_empty()).then_some(now);
+
+        let mut wanted = BTreeMap::new();
+        for (lead_minutes, events) in self.calendars.values() {
+            let lead_ms = i64::from(*lead_minutes) * 60_000;
+            for occurrence in calendar::occurrences(events, unix_now, unix_now + CALENDAR_HORIZON_MS + lead_ms) {
+                let id = format!("{}{}@{}", CALENDAR_TIMER_PREFIX, occurrence.uid, occurrence.start_unix_ms);
+                wanted.insert(id, (occurrence.summary, occurrence.start_unix_ms - lead_ms));
+            }
+        }
+
+        let stale: Vec<String> = self
+            .timers
+            .keys()
+            .filter(|id| id.starts_with(CALENDAR_TIMER_PREFIX) && !wanted.contains_key(*id))
+            .cloned()
+            .collect();
+        for id in stale {
+            let _ = self.remove(&id);
+        }
+        self.dismissed_reminders.retain(|id| wanted.contains_key(id));
+
+        for (id, (label, fire_unix_ms)) in wanted {
+            if self.dismissed_reminders.contains(&id) {
+                continue;
+            }
+            // 提前量比剩余时间还长时立即到点
+            let seconds = ceil_seconds((fire_unix_ms - unix_now).max(0) as Millis);
+            let Some(timer) = self.timers.get_mut(&id) else {
+                let _ = self.create(Some(id.clone()), Some(label), TimerKind::Countdown, seconds);
+                let _ = self.start(&id);
+                continue;
+            };
+            if !timer.is_running() {
+                continue;
+            }
+            if timer.label != label {
+                timer.label = label;
+                self.timers_revision += 1;
+            }
+            let drift = timer.deadline.map_or(0, |deadline| unix_now + deadline as i64 - now as i64 - fire_unix_ms);
+            if drift.abs() > CALENDAR_TOLERANCE_MS {
+                let _ = self.set_total(&id, seconds);
+            }
+        }
+    }
+
+    // 下一次需要调用 poll() 的时刻，没有运行中的计时器和待响的闹钟时为空
+    pub fn next_wakeup(&self) -> Option<Millis> {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let alarm_wakeup = self
+            .alarms
+            .values()
+            .filter_map(|alarm| alarm.next_fire_unix_ms)
+            .min()
+            .map(|fire| now + ((fire - unix_now).max(0) as Millis).min(ALARM_RECHECK_MS));
+        let calendar_wakeup = self.calendar_synced.map(|synced| synced + CALENDAR_RECHECK_MS);
+        self.timers.values().filter_map(|t| t.next_tick).chain(alarm_wakeup).chain(calendar_wakeup).min()
+    }
+
+    // 处理所有已到计划时刻的计时器
+    // 即使宿主迟到很久（例如系统休眠），每个计时器也只补发一次 tick，剩余时间仍由截止时刻推算
+    pub fn poll(&mut self) {
+        let now = self.clock.now_ms();
+        let unix_now = self.clock.unix_ms();
+        let mut events = Vec::new();
+        let mut ended_sessions = Vec::new();
+
+        for (id, timer) in self.timers.iter_mut() {
+            let Some(next_tick) = timer.next_tick else {
+                continue;
+            };
+            if next_tick > now {
+                continue;
+            }
+
+            let drift_ms = (now - next_tick) as i64;
+            timer.last_drift_ms = drift_ms;
+
+            let elapsed_ms = timer.elapsed_at(now);
+            let remaining_seconds = ceil_seconds(timer.remaining_at(now));
+            let overtime_ms = timer.overtime_at(now);
+            events.push(TimerEvent::Tick {
+                id: id.clone(),
+                remaining_seconds,
+                elapsed_seconds: floor_seconds(elapsed_ms),
+                drift_ms,
+                overtime_seconds: floor_seconds(overtime_ms),
+            });
+
+            // 超时阶段按超出时间的整秒继续 tick，直到手动停止
+            if timer.phase == TimerPhase::Overtime {
+                timer.next_tick = Some(next_tick_after(overtime_ms, now));
+                continue;
+            }
+
+            // 秒表没有终点，到达目标时间后继续计时
+            let Some(deadline) = timer.deadline else {
+                timer.next_tick = Some(next_tick_after(elapsed_ms, now));
+                continue;
+            };
+            if remaining_seconds == 0 {
+                // 记录按截止时刻结束，不计入宿主迟到的时间
+                let deadline_unix_ms = unix_now - (now - deadline) as i64;
+                timer.disarm(now);
+                if let Some((ended, next)) = timer.advance_plan() {
+                    ended_sessions.extend(timer.end_session(id, deadline_unix_ms, SessionOutcome::Completed));
+                    // 需要确认的分段即使计划会自动切换也停下等待
+                    let (segment_index, completed_sessions, auto_advance) = timer.plan.as_ref().map_or((0, 0, false), |plan| {
+                        let wait = plan.current().is_some_and(|segment| segment.wait);
+                        (plan.index, plan.completed_sessions, plan.auto_advance && !wait)
+                    });
+                    events.push(TimerEvent::SegmentFinished {
+                        id: id.clone(),
+                        ended: ended.label,
+                        next,
+                        waiting: !auto_advance,
+                        alert: ended.alert,
+                        segment_index,
+                        completed_sessions,
+                        total_seconds: timer.total_seconds,
+                        drift_ms,
+                    });
+                    if auto_advance {
+                        timer.arm(now);
+                        timer.begin_session(unix_now, timer.total_seconds);
+                        events.push(TimerEvent::Started {
+                            id: id.clone(),
+                            remaining_seconds: timer.total_seconds,
+                            total_seconds: timer.total_seconds,
+                        });
+                    } else {
+                        let _ = timer.transition(TimerPhase::Idle);
+                    }
+                    continue;
+                }
+                // 到点只会发生在运行阶段，转换必然合法；超时中的记录留到超时结束
+                if timer.finish.overtime {
+                    let _ = timer.transition(TimerPhase::Overtime);
+                    timer.overtime_since = Some(deadline);
+                    timer.next_tick = Some(next_tick_after(timer.overtime_at(now), now));
+                    events.push(TimerEvent::Overtime { id: id.clone(), drift_ms });
+                } else {
+                    let _ = timer.transition(TimerPhase::Finished);
+                    ended_sessions.extend(timer.end_session(id, deadline_unix_ms, SessionOutcome::Completed));
+                    let last = timer.plan.as_ref().and_then(|plan| plan.current());
+                    let segment = last.map(|segment| segment.label.clone());
+                    let alert = last.map(|segment| segment.alert.clone()).unwrap_or_default();
+                    events.push(TimerEvent::Finished { id: id.clone(), label: timer.label.clone(), segment, alert, drift_ms });
+                }
+            } else {
+                timer.next_tick = Some(next_tick_before(deadline, now));
+            }
+        }
+
+        self.sessions.extend(ended_sessions);
+        self.poll_alarms(&mut events);
+
+        for event in events {
+            self.emit(event);
+        }
+
+        if self.calendar_synced.is_some_and(|synced| now >= synced + CALENDAR_RECHECK_MS) {
+            self.sync_calendars();
+        }
+    }
+
+    // 到点的闹钟响铃，然后按当前时间（和当前时区）重新计算所有闹钟的下一次响铃时刻
+    // 一次性闹钟响过后自动停用
+    fn poll_alarms(&mut self, events: &mut Vec<TimerEvent>) {
+        let unix_now = self.clock.unix_ms();
+        let ids: Vec<String> = self.alarms.keys().cloned().collect();
+        for id in ids {
+            let Some(mut alarm) = self.alarms.get(&id).cloned() else {
+                continue;
+            };
+            if alarm.next_fire_unix_ms.is_some_and(|fire| fire <= unix_now) {
+                events.push(TimerEvent::AlarmFired {
+                    id: id.clone(),
+                    label: alarm.label.clone(),
+                });
+                if matches!(alarm.repeat, AlarmRepeat::Once { .. }) {
+                    alarm.enabled = false;
+                }
+            }
+            alarm.next_fire_unix_ms = self.next_fire(&alarm, unix_now);
+            if self.alarms.get(&id) != Some(&alarm) {
+                self.alarms.insert(id, alarm);
+                self.alarms_revision += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    // 2026-10-17 00:00:00 UTC
+    const START_UNIX_MS: i64 = 1_792_195_200_000;
+    const MINUTE_MS: Millis = 60 * SECOND_MS;
+    const HOUR_MS: Millis = 60 * MINUTE_MS;
+    const DAY_MS: Millis = 24 * HOUR_MS;
+
+    fn engine() -> (TimerEngine<ManualClock>, ManualClock) {
+        let clock = ManualClock::starting_at_unix(START_UNIX_MS);
+        (TimerEngine::new(clock.clone()), clock)
+    }
+
+    // 新建并启动一个倒计时
+    fn countdown(engine: &mut TimerEngine<ManualClock>, seconds: u32) {
+        engine.create(Some("t".to_string()), None, TimerKind::Countdown, seconds).unwrap();
+        engine.start("t").unwrap();
+    }
+
+    // 记录引擎发出的事件，每秒的 tick 除外
+    fn record(engine: &mut TimerEngine<ManualClock>) -> Arc<Mutex<Vec<TimerEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        engine.on_event(move |event| {
+            if !matches!(event, TimerEvent::Tick { .. }) {
+                sink.lock().unwrap().push(event.clone());
+            }
+        });
+        events
+    }
+
+    fn finished_count(events: &Mutex<Vec<TimerEvent>>) -> usize {
+        events.lock().unwrap().iter().filter(|event| matches!(event, TimerEvent::Finished { .. })).count()
+    }
+
+    // 像宿主一样在 next_wakeup() 给出的时刻调用 poll()，一直模拟到 until
+    fn run_until(engine: &mut TimerEngine<ManualClock>, clock: &ManualClock, until: Millis) {
+        let mut polls = 0;
+        while let Some(wakeup) = engine.next_wakeup().filter(|wakeup| *wakeup <= until) {
+            clock.set(wakeup.max(clock.now_ms()));
+            engine.poll();
+            polls += 1;
+            assert!(polls < 1_000_000, "引擎在 {} ms 处空转", clock.now_ms());
+        }
+        clock.set(until);
+        engine.poll();
+    }
+
+    #[test]
+    fn day_long_countdown_finishes_once_at_deadline() {
+        let (mut engine, clock) = engine();
+        let events = record(&mut engine);
+        countdown(&mut engine, 24 * 3600);
+
+        run_until(&mut engine, &clock, DAY_MS - 1);
+        let snapshot = engine.snapshot("t").unwrap();
+        assert_eq!(snapshot.phase, TimerPhase::Running);
+        assert_eq!(snapshot.remaining_seconds, 1);
+        assert_eq!(finished_count(&events), 0);
+
+        run_until(&mut engine, &clock, DAY_MS);
+        assert_eq!(engine.snapshot("t").unwrap().phase, TimerPhase::Finished);
+        assert_eq!(finished_count(&events), 1);
+        assert!(events.lock().unwrap().iter().any(|event| matches!(event, TimerEvent::Finished { drift_ms: 0, .. })));
+
+        // 结束后不会再次结束，也不再需要唤醒
+        run_until(&mut engine, &clock, DAY_MS + HOUR_MS);
+        assert_eq!(finished_count(&events), 1);
+        assert_eq!(engine.next_wakeup(), None);
+        let sessions = engine.take_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].outcome, SessionOutcome::Completed);
+        assert_eq!(sessions[0].actual_ms, DAY_MS);
+        assert_eq!(sessions[0].ended_unix_ms, START_UNIX_MS + DAY_MS as i64);
+    }
+
+    #[test]
+    fn pause_and_resume_across_hours() {
+        let (mut engine, clock) = engine();
+        let events = record(&mut engine);
+        countdown(&mut engine, 2 * 3600);
+
+        run_until(&mut engine, &clock, 30 * MINUTE_MS);
+        engine.pause("t").unwrap();
+        // 暂停期间不需要唤醒，剩余时间保持不变
+        assert_eq!(engine.next_wakeup(), None);
+        run_until(&mut engine, &clock, 5 * HOUR_MS + 30 * MINUTE_MS);
+        assert_eq!(engine.snapshot("t").unwrap().remaining_seconds, 90 * 60);
+
+        engine.resume("t").unwrap();
+        run_until(&mut engine, &clock, 7 * HOUR_MS - 1);
+        assert_eq!(finished_count(&events), 0);
+        run_until(&mut engine, &clock, 7 * HOUR_MS);
+        assert_eq!(finished_count(&events), 1);
+
+        // 实际计时的时长不包括暂停的 5 小时
+        let sessions = engine.take_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].actual_ms, 2 * HOUR_MS);
+    }
+
+    #[test]
+    fn adjust_while_running_moves_the_deadline() {
+        let (mut engine, clock) = engine();
+        let events = record(&mut engine);
+        countdown(&mut engine, 3600);
+
+        run_until(&mut engine, &clock, 10 * MINUTE_MS);
+        let snapshot = engine.adjust("t", 5 * 60).unwrap();
+        assert_eq!(snapshot.remaining_seconds, 55 * 60);
+        assert_eq!(snapshot.total_seconds, 65 * 60);
+
+        run_until(&mut engine, &clock, 65 * MINUTE_MS - 1);
+        assert_eq!(finished_count(&events), 0);
+        run_until(&mut engine, &clock, 65 * MINUTE_MS);
+        assert_eq!(finished_count(&events), 1);
+    }
+
+    #[test]
+    fn adjust_below_zero_finishes_on_next_poll() {
+        let (mut engine, clock) = engine();
+        let events = record(&mut engine);
+        countdown(&mut engine, 600);
+
+        run_until(&mut engine, &clock, MINUTE_MS);
+        assert_eq!(engine.adjust("t", -3600).unwrap().remaining_seconds, 0);
+        run_until(&mut engine, &clock, MINUTE_MS);
+        assert_eq!(finished_count(&events), 1);
+        assert_eq!(engine.snapshot("t").unwrap().phase, TimerPhase::Finished);
+    }
+
+    #[test]
+    fn set_total_while_running_restarts_from_now() {
+        let (mut engine, clock) = engine();
+        let events = record(&mut engine);
+        countdown(&mut engine, 3600);
+
+        run_until(&mut engine, &clock, 20 * MINUTE_MS);
+        engine.set_total("t", 600).unwrap();
+        let snapshot = engine.snapshot("t").unwrap();
+        assert_eq!(snapshot.phase, TimerPhase::Running);
+        assert_eq!(snapshot.remaining_seconds, 600);
+
+        run_until(&mut engine, &clock, 30 * MINUTE_MS - 1);
+        assert_eq!(finished_count(&events), 0);
+        run_until(&mut engine, &clock, 30 * MINUTE_MS);
+        assert_eq!(finished_count(&events), 1);
+
+        // 原来的记录算作取消，新时长另起一条完成的记录
+        let outcomes: Vec<_> = engine.take_sessions().into_iter().map(|session| session.outcome).collect();
+        assert_eq!(outcomes, [SessionOutcome::Cancelled, SessionOutcome::Completed]);
+    }
+
+    #[test]
+    fn restore_reports_deadline_missed_while_closed() {
+        let (mut engine, clock) = engine();
+        countdown(&mut engine, 3600);
+        run_until(&mut engine, &clock, 10 * MINUTE_MS);
+        let snapshot = engine.snapshot("t").unwrap();
+        let deadline_unix_ms = START_UNIX_MS + HOUR_MS as i64;
+        assert_eq!(snapshot.deadline_unix_ms, Some(deadline_unix_ms));
+
+        // 应用关闭 3 小时后重新启动
+        let reopened = ManualClock::starting_at_unix(START_UNIX_MS + 3 * HOUR_MS as i64);
+        let mut restored = TimerEngine::new(reopened.clone());
+        let events = record(&mut restored);
+        restored.restore(snapshot);
+
+        let snapshot = restored.snapshot("t").unwrap();
+        assert_eq!(snapshot.phase, TimerPhase::Finished);
+        assert_eq!(snapshot.missed_deadline_unix_ms, Some(deadline_unix_ms));
+        assert_eq!(snapshot.remaining_seconds, 0);
+        let missed: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                TimerEvent::Missed { deadline_unix_ms, .. } => Some(*deadline_unix_ms),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(missed, [deadline_unix_ms]);
+        // 错过的倒计时不再补发结束事件
+        run_until(&mut restored, &reopened, HOUR_MS);
+        assert_eq!(finished_count(&events), 0);
+
+        // 记录按原来的截止时刻结束
+        let sessions = restored.take_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].outcome, SessionOutcome::Completed);
+        assert_eq!(sessions[0].ended_unix_ms, deadline_unix_ms);
+    }
+
+    #[test]
+    fn restore_before_deadline_keeps_running() {
+        let (mut engine, clock) = engine();
+        countdown(&mut engine, 3600);
+        run_until(&mut engine, &clock, 10 * MINUTE_MS);
+        let snapshot = engine.snapshot("t").unwrap();
+
+        // 20 分钟后重新启动，离截止时刻还有 30 分钟
+        let reopened = ManualClock::starting_at_unix(START_UNIX_MS + 30 * MINUTE_MS as i64);
+        let mut restored = TimerEngine::new(reopened.clone());
+        let events = record(&mut restored);
+        restored.restore(snapshot);
+
+        let snapshot = restored.snapshot("t").unwrap();
+        assert_eq!(snapshot.phase, TimerPhase::Running);
+        assert_eq!(snapshot.remaining_seconds, 30 * 60);
+        assert_eq!(snapshot.missed_deadline_unix_ms, None);
+        run_until(&mut restored, &reopened, 30 * MINUTE_MS);
+        assert_eq!(finished_count(&events), 1);
+    }
+
+    #[test]
+    fn pomodoro_rejects_zero_durations() {
+        let (mut engine, _) = engine();
+        engine.create(Some("t".to_string()), None, TimerKind::Countdown, 60).unwrap();
+        let zero_work = PomodoroConfig { work_seconds: 0, auto_start: true, ..PomodoroConfig::default() };
+        assert!(engine.configure_pomodoro("t", zero_work).is_err());
+        let zero_break = PomodoroConfig { short_break_seconds: 0, auto_start: true, ..PomodoroConfig::default() };
+        assert!(engine.configure_pomodoro("t", zero_break).is_err());
+        let zero_sessions = PomodoroConfig { sessions_before_long_break: 0, ..PomodoroConfig::default() };
+        assert!(engine.configure_pomodoro("t", zero_sessions).is_err());
+        // 被拒绝的设置不会改动计时器
+        assert_eq!(engine.snapshot("t").unwrap().kind, TimerKind::Countdown);
+        engine.poll();
+        assert!(engine.take_sessions().is_empty());
+    }
+
+    #[test]
+    fn interval_rejects_zero_work_and_rounds() {
+        let (mut engine, _) = engine();
+        engine.create(Some("t".to_string()), None, TimerKind::Countdown, 60).unwrap();
+        let mut zero_work = IntervalConfig::default();
+        zero_work.work.seconds = 0;
+        assert!(engine.configure_interval("t", zero_work).is_err());
+        let zero_rounds = IntervalConfig { rounds: 0, ..IntervalConfig::default() };
+        assert!(engine.configure_interval("t", zero_rounds).is_err());
+        assert_eq!(engine.snapshot("t").unwrap().kind, TimerKind::Countdown);
+        // 热身、休息和放松为 0 只是跳过这些分段
+        let mut no_rest = IntervalConfig::default();
+        no_rest.warm_up.seconds = 0;
+        no_rest.rest.seconds = 0;
+        no_rest.cool_down.seconds = 0;
+        assert_eq!(engine.configure_interval("t", no_rest).unwrap().kind, TimerKind::Interval);
+    }
+}
@@ -0,0 +1,153 @@
+// 分段计划的生成：番茄钟等模式都展开成一串按顺序倒数的分段
+
+use beep_protocol::{Alert, IntervalConfig, IntervalStep, Plan, PomodoroConfig, Segment, SequenceConfig};
+
+// 番茄钟专注和休息的圆环颜色
+const WORK_COLOR: &str = "#ef4444";
+const BREAK_COLOR: &str = "#10b981";
+
+// 番茄钟：若干个“专注 + 短休息”，最后一个专注后换成长休息，然后从头循环
+pub fn pomodoro(config: &PomodoroConfig) -> Plan {
+    let sessions = config.sessions_before_long_break.max(1);
+    let mut segments = Vec::new();
+    for session in 1..=sessions {
+        segments.push(Segment {
+            label: "专注".to_string(),
+            seconds: config.work_seconds,
+            session: true,
+            color: Some(WORK_COLOR.to_string()),
+            round: None,
+            wait: false,
+            alert: Alert::default(),
+        });
+        let (label, seconds) = if session == sessions {
+            ("长休息", config.long_break_seconds)
+        } else {
+            ("短休息", config.short_break_seconds)
+        };
+        segments.push(Segment {
+            label: label.to_string(),
+            seconds,
+            session: false,
+            color: Some(BREAK_COLOR.to_string()),
+            round: None,
+            wait: false,
+            alert: Alert::default(),
+        });
+    }
+    Plan {
+        segments,
+        index: 0,
+        repeat: true,
+        auto_advance: config.auto_start,
+        completed_sessions: 0,
+    }
+}
+
+// 校验番茄钟设置：时长为 0 的分段会在同一时刻立即结束，自动开始时计时器会原地空转
+pub fn validate_pomodoro(config: &PomodoroConfig) -> Result<(), String> {
+    if config.work_seconds == 0 {
+        return Err("专注时长必须大于 0".to_string());
+    }
+    if config.short_break_seconds == 0 || config.long_break_seconds == 0 {
+        return Err("休息时长必须大于 0".to_string());
+    }
+    if config.sessions_before_long_break == 0 {
+        return Err("长休息前的专注次数必须大于 0".to_string());
+    }
+    Ok(())
+}
+
+// 间歇训练：热身，每轮运动后休息（最后一轮除外），最后放松；时长为 0 的分段被跳过
+// 运动时长或轮数为 0 时返回错误，其余分段为 0 表示不需要
+pub fn interval(config: &IntervalConfig) -> Result<Plan, String> {
+    if config.work.seconds == 0 {
+        return Err("运动时长必须大于 0".to_string());
+    }
+    if config.rounds == 0 {
+        return Err("轮数必须大于 0".to_string());
+    }
+    let rounds = config.rounds;
+    let segment = |step: &IntervalStep, round: Option<u32>, session: bool| Segment {
+        label: step.label.clone(),
+        seconds: step.seconds,
+        session,
+        color: Some(step.color.clone()),
+        round,
+        wait: false,
+        alert: Alert::default(),
+    };
+
+    let mut segments = Vec::new();
+    if config.warm_up.seconds > 0 {
+        segments.push(segment(&config.warm_up, None, false));
+    }
+    for round in 1..=rounds {
+        // 每完成一段运动计为完成一轮
+        segments.push(segment(&config.work, Some(round), true));
+        if round < rounds && config.rest.seconds > 0 {
+            segments.push(segment(&config.rest, Some(round), false));
+        }
+    }
+    if config.cool_down.seconds > 0 {
+        segments.push(segment(&config.cool_down, None, false));
+    }
+    Ok(Plan {
+        segments,
+        index: 0,
+        repeat: false,
+        auto_advance: true,
+        completed_sessions: 0,
+    })
+}
+
+// 顺序计时：每一步一段，完成一步计为完成一段；需要确认的步骤在上一步结束后停下等待
+pub fn sequence(config: &SequenceConfig) -> Plan {
+    let segments = config
+        .steps
+        .iter()
+        .map(|step| Segment {
+            label: step.label.clone(),
+            seconds: step.seconds,
+            session: true,
+            color: None,
+            round: None,
+            wait: step.wait,
+            alert: step.alert.clone(),
+        })
+        .collect();
+    Plan {
+        segments,
+        index: 0,
+        repeat: false,
+        auto_advance: true,
+        completed_sessions: 0,
+    }
+}
+
+// 校验顺序计时设置，返回可以直接显示给用户的错误
+pub fn validate_sequence(config: &SequenceConfig) -> Result<(), String> {
+    if config.steps.is_empty() {
+        return Err("顺序计时至少需要一个步骤".to_string());
+    }
+    if let Some(step) = config.steps.iter().find(|step| step.seconds == 0) {
+        return Err(format!("步骤“{}”的时长不能为 0", step.label));
+    }
+    for step in &config.steps {
+        if let Some(sound) = &step.alert.sound {
+            validate_sound(sound).map_err(|e| format!("步骤“{}”: {}", step.label, e))?;
+        }
+    }
+    Ok(())
+}
+
+// 声音名称会原样交给系统通知命令，只允许字母、数字、- _ .
+pub fn validate_sound(sound: &str) -> Result<(), String> {
+    let valid = !sound.is_empty()
+        && sound.len() <= 64
+        && sound.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(format!("无效的声音名称“{}”，只能包含字母、数字、-、_ 和 .", sound));
+    }
+    Ok(())
+}
@@ -0,0 +1,263 @@
+// 倒计时预设：有序的命名时长列表，以及 JSON / TOML 文件的导入导出
+// 宿主（Tauri 或浏览器）负责持久化，这里只维护列表本身
+
+use beep_protocol::{Preset, PresetFormat};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::duration::parse_duration;
+
+// 首次使用时的默认预设
+pub fn defaults() -> Vec<Preset> {
+    [("泡茶", 3 * 60), ("站会", 15 * 60), ("深度工作", 50 * 60)]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, seconds))| Preset {
+            id: format!("preset-{}", index + 1),
+            name: name.to_string(),
+            seconds,
+        })
+        .collect()
+}
+
+// 文件中的一条预设，不带 id，导入时重新分配
+// 导出时写秒数；手写文件时也可以用 duration 写成 "25m"、"1:30:00" 等
+#[derive(Serialize, Deserialize)]
+struct PresetEntry {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seconds: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<String>,
+}
+
+impl PresetEntry {
+    fn seconds(&self) -> Result<u32, String> {
+        match (self.seconds, &self.duration) {
+            (Some(seconds), _) => Ok(seconds),
+            (None, Some(duration)) => {
+                parse_duration(duration, &Local::now()).map_err(|e| format!("预设“{}”: {}", self.name, e))
+            }
+            (None, None) => Err(format!("预设“{}”缺少时长", self.name)),
+        }
+    }
+}
+
+// 预设文件：JSON 为 {"presets": [...]}，TOML 为若干个 [[presets]] 表
+#[derive(Serialize, Deserialize)]
+struct PresetFile {
+    presets: Vec<PresetEntry>,
+}
+
+fn validate(name: &str, seconds: u32) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("预设名称不能为空".to_string());
+    }
+    if seconds == 0 {
+        return Err(format!("预设“{}”的时长必须大于 0", name));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+pub struct Presets {
+    items: Vec<Preset>,
+    next_id: u64,
+}
+
+impl Presets {
+    pub fn new(items: Vec<Preset>) -> Self {
+        Self { items, next_id: 0 }
+    }
+
+    pub fn list(&self) -> Vec<Preset> {
+        self.items.clone()
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Preset, String> {
+        self.items
+            .iter()
+            .find(|preset| preset.id == id)
+            .ok_or_else(|| format!("预设不存在: {}", id))
+    }
+
+    fn new_id(&mut self) -> String {
+        loop {
+            self.next_id += 1;
+            let id = format!("preset-{}", self.next_id);
+            if self.items.iter().all(|preset| preset.id != id) {
+                break id;
+            }
+        }
+    }
+
+    // 新建或更新预设，id 为空时追加到末尾
+    pub fn save(&mut self, mut preset: Preset) -> Result<Preset, String> {
+        preset.name = preset.name.trim().to_string();
+        validate(&preset.name, preset.seconds)?;
+        if preset.id.is_empty() {
+            preset.id = self.new_id();
+            self.items.push(preset.clone());
+            return Ok(preset);
+        }
+        let existing = self
+            .items
+            .iter_mut()
+            .find(|existing| existing.id == preset.id)
+            .ok_or_else(|| format!("预设不存在: {}", preset.id))?;
+        *existing = preset.clone();
+        Ok(preset)
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<(), String> {
+        let index = self
+            .items
+            .iter()
+            .position(|preset| preset.id == id)
+            .ok_or_else(|| format!("预设不存在: {}", id))?;
+        self.items.remove(index);
+        Ok(())
+    }
+
+    // 按 ids 的顺序重新排列，ids 必须恰好是现有预设的一个排列
+    pub fn reorder(&mut self, ids: &[String]) -> Result<Vec<Preset>, String> {
+        if ids.len() != self.items.len() {
+            return Err("排序列表与现有预设不一致".to_string());
+        }
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            let preset = self.get(id)?.clone();
+            if items.iter().any(|item: &Preset| item.id == preset.id) {
+                return Err(format!("排序列表中重复的预设: {}", id));
+            }
+            items.push(preset);
+        }
+        self.items = items;
+        Ok(self.list())
+    }
+
+    pub fn export(&self, format: PresetFormat) -> Result<String, String> {
+        let file = PresetFile {
+            presets: self
+                .items
+                .iter()
+                .map(|preset| PresetEntry { name: preset.name.clone(), seconds: Some(preset.seconds), duration: None })
+                .collect(),
+        };
+        match format {
+            PresetFormat::Json => serde_json::to_string_pretty(&file).map_err(|e| e.to_string()),
+            PresetFormat::Toml => toml::to_string(&file).map_err(|e| e.to_string()),
+        }
+    }
+
+    // 导入预设文件，整份文件校验通过后才会修改列表
+    pub fn import(&mut self, format: PresetFormat, text: &str, replace: bool) -> Result<Vec<Preset>, String> {
+        let file: PresetFile = match format {
+            PresetFormat::Json => serde_json::from_str(text).map_err(|e| format!("无法解析 JSON 预设文件: {}", e))?,
+            PresetFormat::Toml => toml::from_str(text).map_err(|e| format!("无法解析 TOML 预设文件: {}", e))?,
+        };
+        let mut imported = Vec::with_capacity(file.presets.len());
+        for entry in &file.presets {
+            let seconds = entry.seconds()?;
+            validate(&entry.name, seconds)?;
+            imported.push((entry.name.trim().to_string(), seconds));
+        }
+        if replace {
+            self.items.clear();
+        }
+        for (name, seconds) in imported {
+            let id = self.new_id();
+            self.items.push(Preset { id, name, seconds });
+        }
+        Ok(self.list())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(presets: &[Preset]) -> Vec<(&str, u32)> {
+        presets.iter().map(|preset| (preset.name.as_str(), preset.seconds)).collect()
+    }
+
+    fn sample() -> Presets {
+        let mut presets = Presets::new(defaults());
+        presets.save(Preset { id: String::new(), name: "带 \"引号\", 逗号".to_string(), seconds: 90 }).unwrap();
+        presets
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let original = sample();
+        let text = original.export(PresetFormat::Json).unwrap();
+        let mut imported = Presets::default();
+        let list = imported.import(PresetFormat::Json, &text, true).unwrap();
+        assert_eq!(entries(&list), entries(&original.list()));
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let original = sample();
+        let text = original.export(PresetFormat::Toml).unwrap();
+        assert!(text.contains("[[presets]]"));
+        let mut imported = Presets::default();
+        let list = imported.import(PresetFormat::Toml, &text, true).unwrap();
+        assert_eq!(entries(&list), entries(&original.list()));
+    }
+
+    #[test]
+    fn import_accepts_duration_text() {
+        let mut presets = Presets::default();
+        let json = r#"{"presets": [{"name": "番茄", "duration": "25m"}, {"name": "长会", "duration": "1:30:00"}]}"#;
+        assert_eq!(entries(&presets.import(PresetFormat::Json, json, true).unwrap()), [("番茄", 1500), ("长会", 5400)]);
+        let toml = "[[presets]]\nname = \" 午休 \"\nduration = \"20 分钟\"\n";
+        let list = presets.import(PresetFormat::Toml, toml, false).unwrap();
+        assert_eq!(entries(&list), [("番茄", 1500), ("长会", 5400), ("午休", 1200)]);
+        // 导入的预设分配新的、互不相同的 id
+        let mut ids: Vec<&str> = list.iter().map(|preset| preset.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let cases = [
+            (PresetFormat::Json, "not json"),
+            (PresetFormat::Json, r#"{"presets": [{"name": "a", "seconds": -1}]}"#),
+            (PresetFormat::Json, r#"{"items": []}"#),
+            (PresetFormat::Json, r#"{"presets": [{"name": "a"}]}"#),
+            (PresetFormat::Json, r#"{"presets": [{"name": "a", "seconds": 0}]}"#),
+            (PresetFormat::Json, r#"{"presets": [{"name": " ", "seconds": 60}]}"#),
+            (PresetFormat::Json, r#"{"presets": [{"name": "a", "duration": "soon"}]}"#),
+            (PresetFormat::Toml, "[[presets]\nname = \"a\""),
+            (PresetFormat::Toml, "[[presets]]\nname = \"a\"\nseconds = \"60\""),
+        ];
+        for (format, text) in cases {
+            let mut presets = sample();
+            let before = presets.list();
+            assert!(presets.import(format, text, true).is_err(), "{:?}", text);
+            // 失败的导入不会改动列表
+            assert_eq!(presets.list(), before);
+        }
+    }
+
+    #[test]
+    fn one_bad_entry_rejects_whole_file() {
+        let mut presets = sample();
+        let before = presets.list();
+        let json = r#"{"presets": [{"name": "a", "seconds": 60}, {"name": "b", "seconds": 0}]}"#;
+        assert!(presets.import(PresetFormat::Json, json, false).is_err());
+        assert_eq!(presets.list(), before);
+    }
+
+    #[test]
+    fn reorder_requires_permutation() {
+        let mut presets = Presets::new(defaults());
+        let ids: Vec<String> = presets.list().into_iter().rev().map(|preset| preset.id).collect();
+        assert_eq!(entries(&presets.reorder(&ids).unwrap()), [("深度工作", 3000), ("站会", 900), ("泡茶", 180)]);
+        assert!(presets.reorder(&ids[..2]).is_err());
+        assert!(presets.reorder(&[ids[0].clone(), ids[0].clone(), ids[1].clone()]).is_err());
+    }
+}
@@ -0,0 +1,467 @@
+// 计时程序文件（.beep）：用文本描述一串依次进行的步骤，可以和训练计划、实验流程一起放进 git
+// 每行一条指令，# 之后到行尾是注释，含空格的文字用双引号括起来（支持 \"、\\ 和 \n）：
+//
+//   name "晨练"
+//   step 热身 5m notify "准备冲刺"
+//   repeat 8 {
+//       step 冲刺 30s sound bell
+//       step 休息 1m30s
+//   }
+//   step 放松 5m wait notify "今天练完了"
+//
+//   name <文字>                    程序名称，最多写一次，不能写在 repeat 中
+//   step <名称> <时长> [选项...]    一个步骤，时长的写法与输入框相同，但不能用 until
+//       wait                       上一步结束后等待确认再开始这一步
+//       sound <声音>               这一步结束时通知使用的系统声音，none 为静音
+//       notify <文字>              这一步结束时通知的正文
+//   repeat <次数> {                重复到对应的 } 为止的步骤，可以嵌套；} 单独占一行
+
+use beep_protocol::{Alert, SequenceConfig, SequenceStep};
+use chrono::Local;
+
+use crate::duration::parse_duration;
+use crate::plan;
+
+// 展开重复后最多的步骤数，防止写错的次数生成一个永远走不完的程序
+const MAX_STEPS: u64 = 1000;
+
+// repeat 最多嵌套的层数
+const MAX_DEPTH: usize = 16;
+
+// 文件中的位置，行和列都从 1 开始，列按字符计
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    // 带位置的错误信息
+    fn error(self, message: impl std::fmt::Display) -> String {
+        format!("第 {} 行第 {} 列：{}", self.line, self.column, message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub position: Position,
+    pub label: String,
+    pub seconds: u32,
+    pub wait: bool,
+    pub alert: Alert,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repeat {
+    pub position: Position,
+    pub count: u32,
+    pub items: Vec<Item>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Item {
+    Step(Step),
+    Repeat(Repeat),
+}
+
+// 解析后的程序，重复块尚未展开
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Program {
+    pub name: Option<String>,
+    pub items: Vec<Item>,
+}
+
+impl Program {
+    // 展开重复块，得到依次进行的步骤；应先通过 validate，否则步骤数没有上限
+    pub fn sequence(&self) -> SequenceConfig {
+        let mut steps = Vec::new();
+        expand(&self.items, &mut steps);
+        SequenceConfig { name: self.name.clone(), steps }
+    }
+}
+
+fn expand(items: &[Item], steps: &mut Vec<SequenceStep>) {
+    for item in items {
+        match item {
+            Item::Step(step) => steps.push(SequenceStep {
+                label: step.label.clone(),
+                seconds: step.seconds,
+                wait: step.wait,
+                alert: step.alert.clone(),
+            }),
+            Item::Repeat(repeat) => {
+                for _ in 0..repeat.count {
+                    expand(&repeat.items, steps);
+                }
+            }
+        }
+    }
+}
+
+// 读取、校验并展开程序文件，得到可以直接交给计时器的顺序计时设置
+pub fn load(text: &str) -> Result<SequenceConfig, String> {
+    let program = parse(text)?;
+    validate(&program)?;
+    Ok(program.sequence())
+}
+
+// 一行中的一个词，引号括起来的文字不会被当成指令或选项
+struct Token {
+    text: String,
+    position: Position,
+    quoted: bool,
+}
+
+impl Token {
+    // 不带引号、内容正好是 word 的词
+    fn is(&self, word: &str) -> bool {
+        !self.quoted && self.text == word
+    }
+}
+
+// 把一行拆成词：空白分隔，{ 和 } 单独成词，# 开始注释
+fn tokenize(line: &str, line_number: usize) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+    while let Some(&(index, c)) = chars.peek() {
+        let position = Position { line: line_number, column: index + 1 };
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '#' {
+            break;
+        } else if c == '{' || c == '}' {
+            chars.next();
+            tokens.push(Token { text: c.to_string(), position, quoted: false });
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(position.error("引号没有闭合")),
+                    Some((_, '"')) => break,
+                    Some((escape, '\\')) => match chars.next() {
+                        Some((_, '"')) => text.push('"'),
+                        Some((_, '\\')) => text.push('\\'),
+                        Some((_, 'n')) => text.push('\n'),
+                        _ => {
+                            let position = Position { line: line_number, column: escape + 1 };
+                            return Err(position.error("无效的转义，只支持 \\\"、\\\\ 和 \\n"));
+                        }
+                    },
+                    Some((_, c)) => text.push(c),
+                }
+            }
+            tokens.push(Token { text, position, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() || matches!(c, '#' | '"' | '{' | '}') {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token { text, position, quoted: false });
+        }
+    }
+    Ok(tokens)
+}
+
+// 解析程序文件，语法错误带行号和列号；时长为 0、空的重复块等问题留给 validate
+pub fn parse(text: &str) -> Result<Program, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut program = Program::default();
+    // 尚未闭合的 repeat：位置、次数和其中已经读到的步骤
+    let mut blocks: Vec<(Position, u32, Vec<Item>)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let tokens = tokenize(line, index + 1)?;
+        let Some((first, args)) = tokens.split_first() else {
+            continue;
+        };
+        let position = first.position;
+        if first.quoted {
+            return Err(position.error("每行应以 name、step、repeat 或 } 开头"));
+        }
+        match first.text.as_str() {
+            "name" => {
+                if !blocks.is_empty() {
+                    return Err(position.error("name 不能写在 repeat 中"));
+                }
+                if program.name.is_some() {
+                    return Err(position.error("程序名称只能写一次"));
+                }
+                let [name] = args else {
+                    return Err(position.error("name 后面应当只有一个名称，含空格时用引号括起来"));
+                };
+                if name.text.trim().is_empty() {
+                    return Err(name.position.error("程序名称不能为空"));
+                }
+                program.name = Some(name.text.clone());
+            }
+            "step" => {
+                let step = parse_step(position, args)?;
+                match blocks.last_mut() {
+                    Some((_, _, items)) => items.push(Item::Step(step)),
+                    None => program.items.push(Item::Step(step)),
+                }
+            }
+            "repeat" => {
+                let count = match args {
+                    [count, open] if open.is("{") => count
+                        .text
+                        .parse::<u32>()
+                        .map_err(|_| count.position.error(format!("重复次数应当是整数，而不是“{}”", count.text)))?,
+                    _ => return Err(position.error("repeat 的写法是 repeat <次数> {")),
+                };
+                if blocks.len() >= MAX_DEPTH {
+                    return Err(position.error(format!("repeat 最多嵌套 {} 层", MAX_DEPTH)));
+                }
+                blocks.push((position, count, Vec::new()));
+            }
+            "}" => {
+                if let Some(extra) = args.first() {
+                    return Err(extra.position.error("} 应当单独占一行"));
+                }
+                let Some((position, count, items)) = blocks.pop() else {
+                    return Err(position.error("多余的 }，前面没有对应的 repeat"));
+                };
+                let repeat = Item::Repeat(Repeat { position, count, items });
+                match blocks.last_mut() {
+                    Some((_, _, items)) => items.push(repeat),
+                    None => program.items.push(repeat),
+                }
+            }
+            keyword => {
+                return Err(position.error(format!("无法识别的指令“{}”，可用的指令有 name、step、repeat", keyword)));
+            }
+        }
+    }
+    if let Some((position, _, _)) = blocks.pop() {
+        return Err(position.error("repeat 缺少对应的 }"));
+    }
+    Ok(program)
+}
+
+// step <名称> <时长> [wait] [sound <声音>] [notify <文字>]
+fn parse_step(position: Position, args: &[Token]) -> Result<Step, String> {
+    let [label, duration, options @ ..] = args else {
+        return Err(position.error("step 需要名称和时长，例如 step 热身 5m"));
+    };
+    let mut step = Step {
+        position,
+        label: label.text.clone(),
+        seconds: parse_seconds(duration)?,
+        wait: false,
+        alert: Alert::default(),
+    };
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        // sound 和 notify 后面紧跟一个值
+        let mut value = |slot: &Option<String>| {
+            if slot.is_some() {
+                return Err(option.position.error(format!("选项 {} 重复", option.text)));
+            }
+            options
+                .next()
+                .map(|value| value.text.clone())
+                .ok_or_else(|| option.position.error(format!("{} 后面缺少内容", option.text)))
+        };
+        if option.is("wait") {
+            step.wait = true;
+        } else if option.is("sound") {
+            step.alert.sound = Some(value(&step.alert.sound)?);
+        } else if option.is("notify") {
+            step.alert.message = Some(value(&step.alert.message)?);
+        } else {
+            return Err(option
+                .position
+                .error(format!("无法识别的选项“{}”，可用的选项有 wait、sound、notify", option.text)));
+        }
+    }
+    Ok(step)
+}
+
+// 程序要能反复运行，时长必须是固定的，不能写成到某个时刻为止
+fn parse_seconds(token: &Token) -> Result<u32, String> {
+    let text = token.text.trim().to_lowercase();
+    let error = |problem: &str| token.position.error(format!("{}，请写成 90s、5m、1h30m 或 25:00", problem));
+    if text.starts_with("until") || text.starts_with('到') {
+        return Err(error("程序中的时长不能用 until"));
+    }
+    parse_duration(&token.text, &Local::now()).map_err(|_| error(&format!("无法识别的时长“{}”", token.text)))
+}
+
+// 检查语法正确的程序能否运行：至少一个步骤、时长和重复次数不为 0、声音名称有效、展开后步骤不会太多
+pub fn validate(program: &Program) -> Result<(), String> {
+    let count = validate_items(&program.items)?;
+    if count == 0 {
+        return Err("程序中没有任何步骤".to_string());
+    }
+    if count > MAX_STEPS {
+        return Err(format!("展开重复后共有 {} 个步骤，最多只能有 {} 个", count, MAX_STEPS));
+    }
+    Ok(())
+}
+
+// 返回展开重复后的步骤数
+fn validate_items(items: &[Item]) -> Result<u64, String> {
+    let mut count: u64 = 0;
+    for item in items {
+        let steps = match item {
+            Item::Step(step) => {
+                validate_step(step)?;
+                1
+            }
+            Item::Repeat(repeat) => {
+                if repeat.count == 0 {
+                    return Err(repeat.position.error("重复次数必须大于 0"));
+                }
+                let inner = validate_items(&repeat.items)?;
+                if inner == 0 {
+                    return Err(repeat.position.error("repeat 中没有任何步骤"));
+                }
+                inner.saturating_mul(u64::from(repeat.count))
+            }
+        };
+        count = count.saturating_add(steps);
+    }
+    Ok(count)
+}
+
+fn validate_step(step: &Step) -> Result<(), String> {
+    let position = step.position;
+    if step.label.trim().is_empty() {
+        return Err(position.error("步骤名称不能为空"));
+    }
+    if step.seconds == 0 {
+        return Err(position.error(format!("步骤“{}”的时长必须大于 0", step.label)));
+    }
+    if let Some(sound) = &step.alert.sound {
+        plan::validate_sound(sound).map_err(|e| position.error(e))?;
+    }
+    if step.alert.message.as_ref().is_some_and(|message| message.trim().is_empty()) {
+        return Err(position.error(format!("步骤“{}”的通知正文不能为空", step.label)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 解析或校验失败时的错误信息
+    fn error(text: &str) -> String {
+        parse(text).and_then(|program| validate(&program)).unwrap_err()
+    }
+
+    #[test]
+    fn loads_valid_program() {
+        let text = "\u{feff}# 晨练\n\
+                    name \"晨练\"\n\
+                    step 热身 5m notify \"准备冲刺\"\n\
+                    repeat 2 {\n\
+                    \x20   step 冲刺 30s sound bell  # 全力\n\
+                    \x20   step \"慢 跑\" 1m30s\n\
+                    }\n\
+                    step 放松 5:00 wait notify \"今天\\n练完了\"\n";
+        let sequence = load(text).unwrap();
+        assert_eq!(sequence.name.as_deref(), Some("晨练"));
+        let steps: Vec<(&str, u32, bool)> =
+            sequence.steps.iter().map(|step| (step.label.as_str(), step.seconds, step.wait)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("热身", 300, false),
+                ("冲刺", 30, false),
+                ("慢 跑", 90, false),
+                ("冲刺", 30, false),
+                ("慢 跑", 90, false),
+                ("放松", 300, true),
+            ]
+        );
+        assert_eq!(sequence.steps[0].alert.message.as_deref(), Some("准备冲刺"));
+        assert_eq!(sequence.steps[1].alert.sound.as_deref(), Some("bell"));
+        assert_eq!(sequence.steps[5].alert.message.as_deref(), Some("今天\n练完了"));
+    }
+
+    #[test]
+    fn nested_repeats_expand_in_order() {
+        let text = "repeat 2 {\nstep a 1m\nrepeat 2 {\nstep b 10s\n}\n}\n";
+        let labels: Vec<String> = load(text).unwrap().steps.into_iter().map(|step| step.label).collect();
+        assert_eq!(labels, ["a", "b", "b", "a", "b", "b"]);
+    }
+
+    #[test]
+    fn keeps_positions() {
+        let program = parse("\n  step a 1m\nrepeat 3 {\n\tstep b 1m\n}").unwrap();
+        let [Item::Step(step), Item::Repeat(repeat)] = program.items.as_slice() else {
+            panic!("unexpected items: {:?}", program.items);
+        };
+        assert_eq!(step.position, Position { line: 2, column: 3 });
+        assert_eq!(repeat.position, Position { line: 3, column: 1 });
+        let [Item::Step(inner)] = repeat.items.as_slice() else {
+            panic!("unexpected items: {:?}", repeat.items);
+        };
+        assert_eq!(inner.position, Position { line: 4, column: 2 });
+    }
+
+    #[test]
+    fn parse_errors_report_line_and_column() {
+        let cases = [
+            ("step a", "第 1 行第 1 列：step 需要名称和时长"),
+            ("step a 5m loud", "第 1 行第 11 列：无法识别的选项“loud”"),
+            ("step \"a 5m", "第 1 行第 6 列：引号没有闭合"),
+            ("step a until 17:30", "第 1 行第 8 列：程序中的时长不能用 until"),
+            ("step a 5x", "第 1 行第 8 列：无法识别的时长“5x”"),
+            ("step a 1m notify", "第 1 行第 11 列：notify 后面缺少内容"),
+            ("step a 1m sound a sound b", "第 1 行第 19 列：选项 sound 重复"),
+            ("step \"a\\t\" 1m", "第 1 行第 8 列：无效的转义"),
+            ("name x\nname y", "第 2 行第 1 列：程序名称只能写一次"),
+            ("name", "第 1 行第 1 列：name 后面应当只有一个名称"),
+            ("name \" \"", "第 1 行第 6 列：程序名称不能为空"),
+            ("repeat 2 {\nname x\n}", "第 2 行第 1 列：name 不能写在 repeat 中"),
+            ("repeat x {", "第 1 行第 8 列：重复次数应当是整数"),
+            ("repeat 2", "第 1 行第 1 列：repeat 的写法是 repeat <次数> {"),
+            ("repeat 3 {\nstep a 1m", "第 1 行第 1 列：repeat 缺少对应的 }"),
+            ("step a 5m\n  }", "第 2 行第 3 列：多余的 }"),
+            ("repeat 2 {\nstep a 1m\n} x", "第 3 行第 3 列：} 应当单独占一行"),
+            ("\"step\" a 1m", "第 1 行第 1 列：每行应以 name、step、repeat 或 } 开头"),
+            ("wait a 1m", "第 1 行第 1 列：无法识别的指令“wait”"),
+        ];
+        for (text, expected) in cases {
+            let error = parse(text).unwrap_err();
+            assert!(error.starts_with(expected), "{:?}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let text = "repeat 2 {\n".repeat(MAX_DEPTH + 1);
+        assert!(parse(&text).unwrap_err().starts_with(&format!("第 {} 行第 1 列：repeat 最多嵌套", MAX_DEPTH + 1)));
+    }
+
+    #[test]
+    fn validation_errors() {
+        let cases = [
+            ("", "程序中没有任何步骤"),
+            ("# 只有注释\nname x", "程序中没有任何步骤"),
+            ("step a 0s", "第 1 行第 1 列：步骤“a”的时长必须大于 0"),
+            ("step \" \" 1m", "第 1 行第 1 列：步骤名称不能为空"),
+            ("step a 1m notify \" \"", "第 1 行第 1 列：步骤“a”的通知正文不能为空"),
+            ("\nstep a 1m sound \"rm -rf\"", "第 2 行第 1 列："),
+            ("repeat 0 {\nstep a 1m\n}", "第 1 行第 1 列：重复次数必须大于 0"),
+            ("step a 1m\nrepeat 2 {\n}", "第 2 行第 1 列：repeat 中没有任何步骤"),
+            ("repeat 1000 {\nstep a 1m\nstep b 1m\n}", "展开重复后共有 2000 个步骤，最多只能有 1000 个"),
+        ];
+        for (text, expected) in cases {
+            let error = error(text);
+            assert!(error.starts_with(expected), "{:?}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn step_count_does_not_overflow() {
+        let text = "repeat 4294967295 {\nrepeat 4294967295 {\nrepeat 4294967295 {\nstep a 1m\n}\n}\n}";
+        assert!(error(text).starts_with("展开重复后共有"));
+    }
+}
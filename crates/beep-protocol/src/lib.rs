@@ -0,0 +1,773 @@
+// 前端（Leptos）与后端（Tauri）之间的 IPC 约定
+// 每个命令的参数、返回值以及每个事件的载荷都在这里定义，两端共用同一份类型
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+// 协议版本，任何不兼容的改动都必须递增
+pub const PROTOCOL_VERSION: u32 = 15;
+
+// 主窗口使用的计时器，后端启动时自动创建
+pub const DEFAULT_TIMER_ID: &str = "default";
+
+// 由日历生成的提醒倒计时的 id 前缀，后面是事件的 UID 和这一次的开始时刻
+pub const CALENDAR_TIMER_PREFIX: &str = "calendar:";
+
+// 计时器生命周期事件的名称
+pub const TIMER_EVENT: &str = "timer_event";
+
+// 一个后端命令：名称、参数类型和返回值类型
+// 参数以 { "args": Args } 的形式传给后端，后端命令统一接收名为 args 的参数
+pub trait IpcCommand {
+    const NAME: &'static str;
+    type Args: Serialize + DeserializeOwned;
+    type Output: Serialize + DeserializeOwned;
+}
+
+// 把参数包装成后端命令期望的形状
+#[derive(Serialize)]
+pub struct Envelope<'a, T> {
+    pub args: &'a T,
+}
+
+// 计时方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerKind {
+    // 从总时间倒数到 0
+    #[default]
+    Countdown,
+    // 从 0 正向计时，总时间作为可选的目标时间（0 表示不设目标）
+    Stopwatch,
+    // 番茄钟：按计划在专注、短休息、长休息之间循环的倒计时
+    Pomodoro,
+    // 间歇训练：热身，若干轮运动和休息，最后放松
+    Interval,
+    // 顺序计时：依次倒数的一串步骤，例如煮 10 分钟 → 焖 5 分钟 → 静置 2 分钟
+    Sequence,
+}
+
+impl TimerKind {
+    // 是否正向计时，其余方式都是倒计时
+    pub fn counts_up(self) -> bool {
+        self == TimerKind::Stopwatch
+    }
+}
+
+// 计划中的一段倒计时
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    pub label: String,
+    pub seconds: u32,
+    // 完成这一段是否计为一个完成的专注（番茄）或一轮训练
+    #[serde(default)]
+    pub session: bool,
+    // 圆环颜色（CSS 颜色），为空时使用默认颜色
+    #[serde(default)]
+    pub color: Option<String>,
+    // 间歇训练中所属的轮次，从 1 开始，热身和放松为空
+    #[serde(default)]
+    pub round: Option<u32>,
+    // 上一段结束后停下等待确认，即使计划会自动切换也不自动开始这一段
+    #[serde(default)]
+    pub wait: bool,
+    // 这一段结束时的提醒方式
+    #[serde(default)]
+    pub alert: Alert,
+}
+
+// 一段结束时的提醒方式，为空时使用默认的声音和通知正文
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    // 系统通知的声音名称，"none" 为静音
+    #[serde(default)]
+    pub sound: Option<String>,
+    // 通知正文
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+// 分段计划：计时器依次倒数每一段，当前段结束后自动切换到下一段
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub segments: Vec<Segment>,
+    // 当前段的下标
+    pub index: u32,
+    // 最后一段结束后是否从头循环
+    pub repeat: bool,
+    // 下一段是否自动开始，否则停在未开始状态等待手动开始
+    pub auto_advance: bool,
+    pub completed_sessions: u32,
+}
+
+impl Plan {
+    pub fn current(&self) -> Option<&Segment> {
+        self.segments.get(self.index as usize)
+    }
+
+    // 计划中的总轮数，没有轮次时为 0
+    pub fn total_rounds(&self) -> u32 {
+        self.segments.iter().filter_map(|segment| segment.round).max().unwrap_or(0)
+    }
+
+    // 当前段之后的下一段，计划结束时为空
+    pub fn upcoming(&self) -> Option<&Segment> {
+        match self.segments.get(self.index as usize + 1) {
+            Some(segment) => Some(segment),
+            None if self.repeat => self.segments.first(),
+            None => None,
+        }
+    }
+}
+
+// 番茄钟设置
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    pub work_seconds: u32,
+    pub short_break_seconds: u32,
+    pub long_break_seconds: u32,
+    // 每完成多少个专注后进行一次长休息
+    pub sessions_before_long_break: u32,
+    // 一段结束后是否自动开始下一段
+    pub auto_start: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_seconds: 25 * 60,
+            short_break_seconds: 5 * 60,
+            long_break_seconds: 15 * 60,
+            sessions_before_long_break: 4,
+            auto_start: false,
+        }
+    }
+}
+
+// 倒计时到点后的处理方式
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinishOptions {
+    // 到点后继续计时，显示超出了多久，直到手动停止
+    pub overtime: bool,
+    // “稍后提醒”重新倒计时的时长
+    pub snooze_seconds: u32,
+}
+
+impl Default for FinishOptions {
+    fn default() -> Self {
+        Self { overtime: false, snooze_seconds: 5 * 60 }
+    }
+}
+
+// 一次计时的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    // 倒计时到点（包括超时后结束），或秒表停止时达到了目标时间
+    Completed,
+    // 删除计时器、切换计时方式或中途重新开始
+    Cancelled,
+    // 未完成时重置
+    Reset,
+}
+
+// 历史记录中的一次计时
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    // 计时器的 id
+    pub id: String,
+    // 计时器名称，分段计划中为分段名称
+    pub label: String,
+    pub kind: TimerKind,
+    pub planned_seconds: u32,
+    // 实际计时的时长，不包括暂停的时间
+    pub actual_ms: u64,
+    pub started_unix_ms: i64,
+    pub ended_unix_ms: i64,
+    pub outcome: SessionOutcome,
+}
+
+// 进行中的一次计时，结束时生成 Session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpenSession {
+    pub label: String,
+    pub planned_seconds: u32,
+    pub started_unix_ms: i64,
+    // 已经结束的暂停累计的时长
+    pub paused_ms: u64,
+    // 当前暂停的开始时刻，未暂停时为空
+    pub paused_since_unix_ms: Option<i64>,
+}
+
+// 计时历史的导出格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionFormat {
+    // 表格，每行一次计时，时间为带时区的 ISO-8601
+    #[default]
+    Csv,
+    // iCalendar 日历，每次计时一个 VEVENT
+    Ics,
+}
+
+impl SessionFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SessionFormat::Csv => "csv",
+            SessionFormat::Ics => "ics",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportSessionsArgs {
+    pub format: SessionFormat,
+    // 导出在 [from_unix_ms, to_unix_ms) 内开始的记录
+    pub from_unix_ms: i64,
+    pub to_unix_ms: i64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ListSessionsArgs {
+    // 只返回在此之后结束的记录，为空时返回全部
+    #[serde(default)]
+    pub since_unix_ms: Option<i64>,
+}
+
+// 秒表的一圈：单圈用时和到这一圈为止的累计用时（分段时间）
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lap {
+    // 从 1 开始的圈数
+    pub index: u32,
+    pub lap_ms: u64,
+    pub split_ms: u64,
+}
+
+// 计时器生命周期阶段
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerPhase {
+    // 创建后或重置后，尚未开始
+    #[default]
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    // 到点后继续正向计时
+    Overtime,
+}
+
+impl TimerPhase {
+    // 检查阶段转换是否合法
+    pub fn can_transition_to(self, next: TimerPhase) -> bool {
+        use TimerPhase::*;
+        matches!(
+            (self, next),
+            // 开始（或重新开始）
+            (Idle | Paused | Finished | Overtime, Running)
+                | (Running, Paused)
+                | (Running, Finished | Overtime)
+                | (Overtime, Finished)
+                // 任何阶段都可以重置
+                | (_, Idle)
+        )
+    }
+}
+
+// 计时器生命周期事件，统一通过 TIMER_EVENT 发送
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimerEvent {
+    Started { id: String, remaining_seconds: u32, total_seconds: u32 },
+    Paused { id: String, remaining_seconds: u32, elapsed_seconds: u32 },
+    Resumed { id: String, remaining_seconds: u32 },
+    Tick {
+        id: String,
+        remaining_seconds: u32,
+        elapsed_seconds: u32,
+        // 本次 tick 实际发生时刻与计划时刻之差（毫秒）
+        drift_ms: i64,
+        // 到点后超出的整秒数，未超时为 0
+        #[serde(default)]
+        overtime_seconds: u32,
+    },
+    Finished {
+        id: String,
+        // 计时器名称，用于通知
+        #[serde(default)]
+        label: String,
+        // 分段计划走完时最后一段的名称，普通倒计时为空
+        #[serde(default)]
+        segment: Option<String>,
+        // 最后一段的提醒方式
+        #[serde(default)]
+        alert: Alert,
+        drift_ms: i64,
+    },
+    // 到点后没有停止，开始记录超时
+    Overtime { id: String, drift_ms: i64 },
+    // 分段计划中的一段结束并切换到下一段，随后如果自动开始还会收到 Started
+    SegmentFinished {
+        id: String,
+        // 刚结束的一段
+        ended: String,
+        // 接下来的一段
+        next: String,
+        // 接下来的一段等待手动开始，不会自动开始
+        #[serde(default)]
+        waiting: bool,
+        // 刚结束的一段的提醒方式
+        #[serde(default)]
+        alert: Alert,
+        segment_index: u32,
+        completed_sessions: u32,
+        total_seconds: u32,
+        drift_ms: i64,
+    },
+    Reset { id: String, remaining_seconds: u32 },
+    // 秒表记录了新的一圈
+    Lap { id: String, lap: Lap },
+    // 运行中或暂停的倒计时被加减了时间
+    Adjusted { id: String, remaining_seconds: u32, total_seconds: u32 },
+    // 闹钟到点，id 为闹钟的 id
+    AlarmFired { id: String, label: String },
+    // 恢复时发现倒计时已在应用关闭期间到点
+    Missed { id: String, label: String, deadline_unix_ms: i64 },
+}
+
+impl TimerEvent {
+    // 事件所属的计时器
+    pub fn id(&self) -> &str {
+        match self {
+            TimerEvent::Started { id, .. }
+            | TimerEvent::Paused { id, .. }
+            | TimerEvent::Resumed { id, .. }
+            | TimerEvent::Tick { id, .. }
+            | TimerEvent::Finished { id, .. }
+            | TimerEvent::Overtime { id, .. }
+            | TimerEvent::SegmentFinished { id, .. }
+            | TimerEvent::Reset { id, .. }
+            | TimerEvent::Lap { id, .. }
+            | TimerEvent::Adjusted { id, .. }
+            | TimerEvent::AlarmFired { id, .. }
+            | TimerEvent::Missed { id, .. } => id,
+        }
+    }
+}
+
+// 计时器的完整快照，前端重新加载或新窗口打开时据此恢复界面
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimerSnapshot {
+    pub id: String,
+    pub label: String,
+    // 旧版本保存的快照没有以下字段，按默认值读取
+    #[serde(default)]
+    pub kind: TimerKind,
+    pub phase: TimerPhase,
+    pub remaining_seconds: u32,
+    pub remaining_ms: u64,
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    pub total_seconds: u32,
+    // 运行中的倒计时的截止时刻（Unix 毫秒），未运行时为空
+    pub deadline_unix_ms: Option<i64>,
+    // 运行中的秒表对应的起点（Unix 毫秒），未运行时为空
+    pub started_unix_ms: Option<i64>,
+    #[serde(default)]
+    pub laps: Vec<Lap>,
+    // 分段计划，普通倒计时和秒表为空
+    #[serde(default)]
+    pub plan: Option<Plan>,
+    // 番茄钟、间歇训练和顺序计时的设置，界面据此显示当前配置
+    #[serde(default)]
+    pub pomodoro: Option<PomodoroConfig>,
+    #[serde(default)]
+    pub interval: Option<IntervalConfig>,
+    #[serde(default)]
+    pub sequence: Option<SequenceConfig>,
+    // 到点后的处理方式，以及超时阶段已经超出的时间
+    #[serde(default)]
+    pub finish: FinishOptions,
+    #[serde(default)]
+    pub overtime_ms: u64,
+    // 应用关闭期间错过的截止时刻，重新开始或重置后清除
+    #[serde(default)]
+    pub missed_deadline_unix_ms: Option<i64>,
+    // 尚未结束的计时记录，随快照保存以便重新启动后继续记录
+    #[serde(default)]
+    pub session: Option<OpenSession>,
+    pub last_drift_ms: i64,
+}
+
+// 版本握手的结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    pub version: u32,
+    // 系统通知上的“稍后提醒”“开始下一步”按钮是否可用，不可用时界面提示回到应用内操作
+    #[serde(default)]
+    pub notification_actions: bool,
+}
+
+// 不需要参数的命令，后端对应的函数不接收 args
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NoArgs {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PingArgs {
+    pub message: String,
+}
+
+// 只需要计时器 id 的命令参数
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimerIdArgs {
+    pub id: String,
+}
+
+impl TimerIdArgs {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+// 创建计时器，未指定 id 时由后端分配
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateTimerArgs {
+    pub id: Option<String>,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub kind: TimerKind,
+    pub seconds: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetTotalSecondsArgs {
+    pub id: String,
+    pub seconds: u32,
+}
+
+// 切换计时方式，计时器会回到未开始状态
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetTimerKindArgs {
+    pub id: String,
+    pub kind: TimerKind,
+}
+
+// 把计时器设为番茄钟，从第一段专注开始
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigurePomodoroArgs {
+    pub id: String,
+    pub config: PomodoroConfig,
+}
+
+// 间歇训练中的一种分段
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntervalStep {
+    pub label: String,
+    // 时长为 0 的热身或放松会被跳过
+    pub seconds: u32,
+    pub color: String,
+}
+
+impl IntervalStep {
+    fn new(label: &str, seconds: u32, color: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            seconds,
+            color: color.to_string(),
+        }
+    }
+}
+
+// 间歇训练设置，最后一轮运动之后不再休息，直接进入放松
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntervalConfig {
+    pub warm_up: IntervalStep,
+    pub work: IntervalStep,
+    pub rest: IntervalStep,
+    pub cool_down: IntervalStep,
+    pub rounds: u32,
+}
+
+impl Default for IntervalConfig {
+    fn default() -> Self {
+        Self {
+            warm_up: IntervalStep::new("热身", 5 * 60, "#f59e0b"),
+            work: IntervalStep::new("运动", 40, "#ef4444"),
+            rest: IntervalStep::new("休息", 20, "#10b981"),
+            cool_down: IntervalStep::new("放松", 5 * 60, "#3b82f6"),
+            rounds: 8,
+        }
+    }
+}
+
+// 把计时器设为间歇训练，从第一段开始
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigureIntervalArgs {
+    pub id: String,
+    pub config: IntervalConfig,
+}
+
+// 顺序计时中的一步
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceStep {
+    pub label: String,
+    pub seconds: u32,
+    // 上一步结束后等待确认再开始这一步，否则自动接着开始
+    #[serde(default)]
+    pub wait: bool,
+    // 这一步结束时的提醒方式
+    #[serde(default)]
+    pub alert: Alert,
+}
+
+impl SequenceStep {
+    fn new(label: &str, seconds: u32) -> Self {
+        Self {
+            label: label.to_string(),
+            seconds,
+            wait: false,
+            alert: Alert::default(),
+        }
+    }
+}
+
+// 顺序计时设置，步骤依次进行，最后一步结束后整个计时结束
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceConfig {
+    // 从程序文件载入时的程序名称
+    #[serde(default)]
+    pub name: Option<String>,
+    pub steps: Vec<SequenceStep>,
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            steps: vec![
+                SequenceStep::new("煮", 10 * 60),
+                SequenceStep::new("焖", 5 * 60),
+                SequenceStep::new("静置", 2 * 60),
+            ],
+        }
+    }
+}
+
+// 把计时器设为顺序计时，从第一步开始
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigureSequenceArgs {
+    pub id: String,
+    pub config: SequenceConfig,
+}
+
+// 载入程序文件（.beep），把计时器设为其中的顺序计时并立即开始
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunProgramArgs {
+    pub id: String,
+    pub path: String,
+}
+
+// 闹钟的重复规则，星期用 1（周一）到 7（周日）表示
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlarmRepeat {
+    // 只在指定日期响一次，响过后自动停用
+    Once { year: i32, month: u32, day: u32 },
+    Daily,
+    // 每周的指定几天，例如工作日为 [1, 2, 3, 4, 5]
+    Weekly { weekdays: Vec<u32> },
+    // 每月的第几天，月份没有这一天时在当月最后一天响
+    MonthlyDay { day: u32 },
+    // 每月第几个星期几，week 为 1 到 4，-1 表示最后一个
+    MonthlyWeekday { week: i32, weekday: u32 },
+}
+
+// 按墙上时间（本地时区）响的闹钟
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alarm {
+    // 新建时留空，由后端分配
+    #[serde(default)]
+    pub id: String,
+    pub label: String,
+    pub hour: u32,
+    pub minute: u32,
+    pub repeat: AlarmRepeat,
+    pub enabled: bool,
+    // 下一次响铃的时刻（Unix 毫秒），由后端计算，停用或不会再响时为空
+    #[serde(default)]
+    pub next_fire_unix_ms: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlarmIdArgs {
+    pub id: String,
+}
+
+// 导入的日历文件，文件有变化时自动重新读取
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarSource {
+    pub path: String,
+    // 提醒倒计时在事件开始前多少分钟到点
+    pub lead_minutes: u32,
+    // 上一次读取到的事件数，以及全天、已取消或重复规则不受支持而跳过的事件数
+    #[serde(default)]
+    pub events: u32,
+    #[serde(default)]
+    pub skipped: u32,
+    // 上一次读取失败的原因，此时继续使用之前读到的事件
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportCalendarArgs {
+    pub path: String,
+    pub lead_minutes: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CalendarPathArgs {
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetFinishOptionsArgs {
+    pub id: String,
+    pub options: FinishOptions,
+}
+
+// 给倒计时加减时间，delta_seconds 为负时减少
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdjustTimerArgs {
+    pub id: String,
+    pub delta_seconds: i64,
+}
+
+// 命名的倒计时预设，例如“泡茶 3 分钟”
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    // 新建时留空，由后端分配
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub seconds: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresetIdArgs {
+    pub id: String,
+}
+
+// 按给定的 id 顺序重新排列预设，必须包含全部预设
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReorderPresetsArgs {
+    pub ids: Vec<String>,
+}
+
+// 预设文件的格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetFormat {
+    #[default]
+    Json,
+    Toml,
+}
+
+impl PresetFormat {
+    // 导出文件的扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            PresetFormat::Json => "json",
+            PresetFormat::Toml => "toml",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportPresetsArgs {
+    pub format: PresetFormat,
+}
+
+// 导入预设文件，replace 为真时替换现有预设，否则追加在后面
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportPresetsArgs {
+    pub format: PresetFormat,
+    pub text: String,
+    #[serde(default)]
+    pub replace: bool,
+}
+
+// 用预设启动计时器：切换为倒计时、设为预设时长并立即开始
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LaunchPresetArgs {
+    pub id: String,
+    pub preset_id: String,
+}
+
+// 系统通知的标题和正文
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NotificationArgs {
+    pub title: String,
+    pub body: String,
+}
+
+// 声明一个命令类型
+macro_rules! ipc_command {
+    ($ty:ident, $name:literal, $args:ty => $output:ty) => {
+        pub struct $ty;
+
+        impl IpcCommand for $ty {
+            const NAME: &'static str = $name;
+            type Args = $args;
+            type Output = $output;
+        }
+    };
+}
+
+// 测试命令 - 用于前端验证Tauri API连接
+ipc_command!(Ping, "ping", PingArgs => String);
+// 启动时的版本握手
+ipc_command!(ProtocolVersion, "protocol_version", NoArgs => ProtocolInfo);
+ipc_command!(CreateTimer, "create_timer", CreateTimerArgs => TimerSnapshot);
+ipc_command!(ListTimers, "list_timers", NoArgs => Vec<TimerSnapshot>);
+ipc_command!(GetTimerState, "get_timer_state", TimerIdArgs => TimerSnapshot);
+ipc_command!(StartTimer, "start_timer", TimerIdArgs => ());
+ipc_command!(ResumeTimer, "resume_timer", TimerIdArgs => ());
+ipc_command!(PauseTimer, "pause_timer", TimerIdArgs => ());
+ipc_command!(ResetTimer, "reset_timer", TimerIdArgs => ());
+ipc_command!(DeleteTimer, "delete_timer", TimerIdArgs => ());
+ipc_command!(SetTotalSeconds, "set_total_seconds", SetTotalSecondsArgs => ());
+ipc_command!(SetTimerKind, "set_timer_kind", SetTimerKindArgs => TimerSnapshot);
+// 不重新开始地延长或缩短倒计时，返回调整后的快照
+ipc_command!(AdjustTimer, "adjust_timer", AdjustTimerArgs => TimerSnapshot);
+// 到点后（包括超时中）稍后提醒：按设置的时长重新倒计时
+ipc_command!(SnoozeTimer, "snooze_timer", TimerIdArgs => ());
+ipc_command!(SetFinishOptions, "set_finish_options", SetFinishOptionsArgs => TimerSnapshot);
+// 计时历史，按结束时间先后排列
+ipc_command!(ListSessions, "list_sessions", ListSessionsArgs => Vec<Session>);
+// 弹出保存对话框导出计时历史，返回保存的文件路径，取消保存时为空
+ipc_command!(ExportSessions, "export_sessions", ExportSessionsArgs => Option<String>);
+// 秒表记一圈
+ipc_command!(LapTimer, "lap_timer", TimerIdArgs => Lap);
+ipc_command!(ConfigurePomodoro, "configure_pomodoro", ConfigurePomodoroArgs => TimerSnapshot);
+ipc_command!(ConfigureInterval, "configure_interval", ConfigureIntervalArgs => TimerSnapshot);
+ipc_command!(ConfigureSequence, "configure_sequence", ConfigureSequenceArgs => TimerSnapshot);
+ipc_command!(RunProgram, "run_program", RunProgramArgs => TimerSnapshot);
+// 新建或更新闹钟，返回带有下一次响铃时间的闹钟
+ipc_command!(SaveAlarm, "save_alarm", Alarm => Alarm);
+ipc_command!(ListAlarms, "list_alarms", NoArgs => Vec<Alarm>);
+ipc_command!(DeleteAlarm, "delete_alarm", AlarmIdArgs => ());
+// 导入日历文件，已经导入过的文件更新提前量；返回读取结果
+ipc_command!(ImportCalendar, "import_calendar", ImportCalendarArgs => CalendarSource);
+ipc_command!(ListCalendars, "list_calendars", NoArgs => Vec<CalendarSource>);
+ipc_command!(RemoveCalendar, "remove_calendar", CalendarPathArgs => ());
+ipc_command!(ListPresets, "list_presets", NoArgs => Vec<Preset>);
+// 新建或更新预设
+ipc_command!(SavePreset, "save_preset", Preset => Preset);
+ipc_command!(DeletePreset, "delete_preset", PresetIdArgs => ());
+ipc_command!(ReorderPresets, "reorder_presets", ReorderPresetsArgs => Vec<Preset>);
+// 导出为文件内容，导入后返回新的完整列表
+ipc_command!(ExportPresets, "export_presets", ExportPresetsArgs => String);
+ipc_command!(ImportPresets, "import_presets", ImportPresetsArgs => Vec<Preset>);
+ipc_command!(LaunchPreset, "launch_preset", LaunchPresetArgs => TimerSnapshot);
+ipc_command!(TriggerNotification, "trigger_notification", NotificationArgs => ());
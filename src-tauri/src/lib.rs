@@ -1,123 +1,404 @@
 use std::process::Command;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
 
+use beep_protocol::{
+    AdjustTimerArgs, Alarm, AlarmIdArgs, CalendarPathArgs, CalendarSource, ConfigureIntervalArgs, ConfigurePomodoroArgs,
+    ConfigureSequenceArgs, CreateTimerArgs, ExportPresetsArgs, ExportSessionsArgs, ImportCalendarArgs, ImportPresetsArgs,
+    IpcCommand, Lap, LaunchPresetArgs, ListSessionsArgs, NotificationArgs, PingArgs, Preset, PresetIdArgs, ProtocolInfo,
+    ReorderPresetsArgs, RunProgramArgs, SetFinishOptionsArgs, Session, SetTimerKindArgs, SetTotalSecondsArgs, TimerIdArgs,
+    TimerSnapshot, PROTOCOL_VERSION,
+};
 // 修正导入语句，Tauri v2中run函数位于tauri::Builder
-use tauri::{command, AppHandle, Emitter, Builder, Runtime};
+use tauri::{command, AppHandle, Builder, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
 
-// 定义计时器状态结构体
-#[derive(Default)]
-struct TimerState {
-    remaining_seconds: u32,
-    total_seconds: u32,
-    is_running: bool,
-}
+mod presets;
+mod storage;
+mod timer;
+
+use presets::PresetStore;
+use timer::TimerHandle;
 
 // 删除重复的run函数定义
 
+// 编译期检查：命令函数名必须与 beep-protocol 中声明的名称一致
+const fn same_name(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+macro_rules! assert_command_names {
+    ($($func:ident => $cmd:ty),* $(,)?) => {
+        const _: () = {
+            $(assert!(same_name(stringify!($func), <$cmd as IpcCommand>::NAME), concat!("命令名与协议不一致: ", stringify!($func)));)*
+        };
+    };
+}
+
+assert_command_names! {
+    ping => beep_protocol::Ping,
+    protocol_version => beep_protocol::ProtocolVersion,
+    create_timer => beep_protocol::CreateTimer,
+    list_timers => beep_protocol::ListTimers,
+    get_timer_state => beep_protocol::GetTimerState,
+    start_timer => beep_protocol::StartTimer,
+    resume_timer => beep_protocol::ResumeTimer,
+    pause_timer => beep_protocol::PauseTimer,
+    reset_timer => beep_protocol::ResetTimer,
+    delete_timer => beep_protocol::DeleteTimer,
+    set_total_seconds => beep_protocol::SetTotalSeconds,
+    set_timer_kind => beep_protocol::SetTimerKind,
+    adjust_timer => beep_protocol::AdjustTimer,
+    snooze_timer => beep_protocol::SnoozeTimer,
+    set_finish_options => beep_protocol::SetFinishOptions,
+    list_sessions => beep_protocol::ListSessions,
+    export_sessions => beep_protocol::ExportSessions,
+    lap_timer => beep_protocol::LapTimer,
+    configure_pomodoro => beep_protocol::ConfigurePomodoro,
+    configure_interval => beep_protocol::ConfigureInterval,
+    configure_sequence => beep_protocol::ConfigureSequence,
+    run_program => beep_protocol::RunProgram,
+    save_alarm => beep_protocol::SaveAlarm,
+    list_alarms => beep_protocol::ListAlarms,
+    delete_alarm => beep_protocol::DeleteAlarm,
+    import_calendar => beep_protocol::ImportCalendar,
+    list_calendars => beep_protocol::ListCalendars,
+    remove_calendar => beep_protocol::RemoveCalendar,
+    list_presets => beep_protocol::ListPresets,
+    save_preset => beep_protocol::SavePreset,
+    delete_preset => beep_protocol::DeletePreset,
+    reorder_presets => beep_protocol::ReorderPresets,
+    export_presets => beep_protocol::ExportPresets,
+    import_presets => beep_protocol::ImportPresets,
+    launch_preset => beep_protocol::LaunchPreset,
+    trigger_notification => beep_protocol::TriggerNotification,
+}
+
 // 测试命令 - 用于前端验证Tauri API连接
 #[command]
-fn ping(message: String) -> Result<String, String> {
-    Ok(format!("Pong: {}", message))
+fn ping(args: PingArgs) -> Result<String, String> {
+    Ok(format!("Pong: {}", args.message))
 }
 
-// 启动计时器
+// 版本握手，前端启动时比较双方的协议版本
 #[command]
-fn start_timer(app: AppHandle, state: tauri::State<Arc<Mutex<TimerState>>>, seconds: u32) -> Result<(), String> {
-    let state_clone = Arc::clone(&state.inner());
-    let app_clone = app.clone();
+fn protocol_version() -> Result<ProtocolInfo, String> {
+    Ok(ProtocolInfo { version: PROTOCOL_VERSION, notification_actions: NOTIFICATION_ACTIONS })
+}
 
-    let mut current_state = state_clone.lock().map_err(|e| format!("无法获取锁: {}", e))?;
-    if current_state.is_running {
-        return Ok(());
-    }
-    current_state.remaining_seconds = seconds;
-    current_state.total_seconds = seconds;
-    current_state.is_running = true;
-    drop(current_state);
-
-    thread::spawn(move || {
-        loop {
-            let mut current_state = match state_clone.lock() {
-                Ok(s) => s,
-                Err(e) => {
-                    eprintln!("获取锁失败: {}", e);
-                    break;
-                }
-            };
-            
-            if !current_state.is_running {
-                break;
-            }
-            
-            if current_state.remaining_seconds > 0 {
-                current_state.remaining_seconds -= 1;
-                let remaining = current_state.remaining_seconds;
-                let _ = app_clone.emit_to("main", "timer_update", remaining);
-            } else {
-                current_state.is_running = false;
-                let _ = trigger_notification();
-                break;
-            }
-            
-            drop(current_state);
-            thread::sleep(Duration::from_secs(1));
-        }
-    });
+// 创建计时器，未指定 id 时自动分配
+#[command]
+async fn create_timer(timers: tauri::State<'_, TimerHandle>, args: CreateTimerArgs) -> Result<TimerSnapshot, String> {
+    timers.create(args.id, args.label, args.kind, args.seconds).await
+}
 
-    Ok(())
+// 列出所有计时器
+#[command]
+async fn list_timers(timers: tauri::State<'_, TimerHandle>) -> Result<Vec<TimerSnapshot>, String> {
+    timers.list().await
+}
+
+// 查询计时器的完整快照，前端挂载或重新连接时用来同步界面
+#[command]
+async fn get_timer_state(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<TimerSnapshot, String> {
+    timers.get(args.id).await
+}
+
+// 启动计时器，从总时间重新开始
+#[command]
+async fn start_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.start(args.id).await
+}
+
+// 继续计时器，保留暂停时的剩余时间
+#[command]
+async fn resume_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.resume(args.id).await
 }
 
 // 暂停计时器
 #[command]
-fn pause_timer(state: tauri::State<Arc<Mutex<TimerState>>>) -> Result<(), String> {
-    let mut timer_state = state.inner().lock().map_err(|e| format!("无法获取锁: {}", e))?;
-    timer_state.is_running = false;
-    Ok(())
+async fn pause_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.pause(args.id).await
 }
 
 // 重置计时器
 #[command]
-fn reset_timer(state: tauri::State<Arc<Mutex<TimerState>>>) -> Result<(), String> {
-    let mut timer_state = state.inner().lock().map_err(|e| format!("无法获取锁: {}", e))?;
-    timer_state.remaining_seconds = timer_state.total_seconds;
-    timer_state.is_running = false;
-    Ok(())
+async fn reset_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.reset(args.id).await
+}
+
+// 删除计时器
+#[command]
+async fn delete_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.delete(args.id).await
 }
 
 // 设置总时间
 #[command]
-fn set_total_seconds(state: tauri::State<Arc<Mutex<TimerState>>>, seconds: u32) -> Result<(), String> {
-    let mut timer_state = state.inner().lock().map_err(|e| format!("无法获取锁: {}", e))?;
-    timer_state.total_seconds = seconds;
-    timer_state.remaining_seconds = seconds;
-    Ok(())
+async fn set_total_seconds(timers: tauri::State<'_, TimerHandle>, args: SetTotalSecondsArgs) -> Result<(), String> {
+    timers.set_total(args.id, args.seconds).await
+}
+
+// 切换倒计时 / 秒表
+#[command]
+async fn set_timer_kind(timers: tauri::State<'_, TimerHandle>, args: SetTimerKindArgs) -> Result<TimerSnapshot, String> {
+    timers.set_kind(args.id, args.kind).await
+}
+
+// 给运行中或暂停的倒计时加减时间
+#[command]
+async fn adjust_timer(timers: tauri::State<'_, TimerHandle>, args: AdjustTimerArgs) -> Result<TimerSnapshot, String> {
+    timers.adjust(args.id, args.delta_seconds).await
+}
+
+// 已结束或超时中的倒计时稍后提醒
+#[command]
+async fn snooze_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<(), String> {
+    timers.snooze(args.id).await
+}
+
+// 设置到点后是否继续超时计时，以及稍后提醒的时长
+#[command]
+async fn set_finish_options(
+    timers: tauri::State<'_, TimerHandle>,
+    args: SetFinishOptionsArgs,
+) -> Result<TimerSnapshot, String> {
+    timers.set_finish_options(args.id, args.options).await
+}
+
+// 秒表记一圈
+#[command]
+async fn lap_timer(timers: tauri::State<'_, TimerHandle>, args: TimerIdArgs) -> Result<Lap, String> {
+    timers.lap(args.id).await
+}
+
+// 设为番茄钟
+#[command]
+async fn configure_pomodoro(
+    timers: tauri::State<'_, TimerHandle>,
+    args: ConfigurePomodoroArgs,
+) -> Result<TimerSnapshot, String> {
+    timers.configure_pomodoro(args.id, args.config).await
+}
+
+// 设为间歇训练
+#[command]
+async fn configure_interval(
+    timers: tauri::State<'_, TimerHandle>,
+    args: ConfigureIntervalArgs,
+) -> Result<TimerSnapshot, String> {
+    timers.configure_interval(args.id, args.config).await
+}
+
+// 设为顺序计时，步骤依次进行
+#[command]
+async fn configure_sequence(
+    timers: tauri::State<'_, TimerHandle>,
+    args: ConfigureSequenceArgs,
+) -> Result<TimerSnapshot, String> {
+    timers.configure_sequence(args.id, args.config).await
+}
+
+// 载入程序文件（.beep）并立即开始其中的第一步
+#[command]
+async fn run_program(timers: tauri::State<'_, TimerHandle>, args: RunProgramArgs) -> Result<TimerSnapshot, String> {
+    let text = std::fs::read_to_string(&args.path).map_err(|e| format!("读取 {} 失败: {}", args.path, e))?;
+    let config = beep_engine::program::load(&text).map_err(|e| format!("{}：{}", args.path, e))?;
+    timers.start_sequence(args.id, config).await
+}
+
+// 新建或更新闹钟
+#[command]
+async fn save_alarm(timers: tauri::State<'_, TimerHandle>, args: Alarm) -> Result<Alarm, String> {
+    timers.save_alarm(args).await
+}
+
+// 列出所有闹钟，带有下一次响铃时刻
+#[command]
+async fn list_alarms(timers: tauri::State<'_, TimerHandle>) -> Result<Vec<Alarm>, String> {
+    timers.list_alarms().await
+}
+
+// 删除闹钟
+#[command]
+async fn delete_alarm(timers: tauri::State<'_, TimerHandle>, args: AlarmIdArgs) -> Result<(), String> {
+    timers.delete_alarm(args.id).await
+}
+
+// 导入本地日历文件，为即将开始的事件安排提醒倒计时
+#[command]
+async fn import_calendar(timers: tauri::State<'_, TimerHandle>, args: ImportCalendarArgs) -> Result<CalendarSource, String> {
+    timers.import_calendar(args.path, args.lead_minutes).await
+}
+
+// 列出导入的日历文件和上一次读取的结果
+#[command]
+async fn list_calendars(timers: tauri::State<'_, TimerHandle>) -> Result<Vec<CalendarSource>, String> {
+    timers.list_calendars().await
+}
+
+// 移除日历文件，由它生成的提醒随之删除
+#[command]
+async fn remove_calendar(timers: tauri::State<'_, TimerHandle>, args: CalendarPathArgs) -> Result<(), String> {
+    timers.remove_calendar(args.path).await
+}
+
+// 读取计时历史，只返回 since_unix_ms 之后结束的记录
+#[command]
+fn list_sessions(app: AppHandle, args: ListSessionsArgs) -> Result<Vec<Session>, String> {
+    let sessions = storage::load_lines::<Session>(&app, timer::HISTORY_FILE)?;
+    Ok(sessions
+        .into_iter()
+        .filter(|session| args.since_unix_ms.is_none_or(|since| session.ended_unix_ms >= since))
+        .collect())
+}
+
+// 把一段日期内的计时历史导出为 CSV 或 iCalendar，弹出保存对话框让用户选择位置
+#[command]
+async fn export_sessions(app: AppHandle, args: ExportSessionsArgs) -> Result<Option<String>, String> {
+    let sessions = storage::load_lines::<Session>(&app, timer::HISTORY_FILE)?;
+    let sessions = beep_engine::history::in_range(sessions, args.from_unix_ms, args.to_unix_ms);
+    let text = beep_engine::history::export(&sessions, args.format, &chrono::Local, chrono::Utc::now().timestamp_millis());
+
+    let extension = args.format.extension();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("beep-history.{}", extension))
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    // 用户取消时不写文件
+    let Some(path) = rx.await.map_err(|_| "保存对话框已关闭".to_string())? else {
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(|e| format!("无法保存到所选位置: {}", e))?;
+    std::fs::write(&path, text).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+    Ok(Some(path.display().to_string()))
+}
+
+// 列出所有预设，按界面上的顺序
+#[command]
+fn list_presets(presets: tauri::State<'_, PresetStore>) -> Result<Vec<Preset>, String> {
+    presets.read(|presets| Ok(presets.list()))
+}
+
+// 新建或更新预设
+#[command]
+fn save_preset(presets: tauri::State<'_, PresetStore>, args: Preset) -> Result<Preset, String> {
+    presets.update(|presets| presets.save(args))
+}
+
+// 删除预设
+#[command]
+fn delete_preset(presets: tauri::State<'_, PresetStore>, args: PresetIdArgs) -> Result<(), String> {
+    presets.update(|presets| presets.delete(&args.id))
+}
+
+// 调整预设顺序
+#[command]
+fn reorder_presets(presets: tauri::State<'_, PresetStore>, args: ReorderPresetsArgs) -> Result<Vec<Preset>, String> {
+    presets.update(|presets| presets.reorder(&args.ids))
+}
+
+// 导出预设为 JSON 或 TOML 文本
+#[command]
+fn export_presets(presets: tauri::State<'_, PresetStore>, args: ExportPresetsArgs) -> Result<String, String> {
+    presets.read(|presets| presets.export(args.format))
+}
+
+// 从 JSON 或 TOML 文本导入预设
+#[command]
+fn import_presets(presets: tauri::State<'_, PresetStore>, args: ImportPresetsArgs) -> Result<Vec<Preset>, String> {
+    presets.update(|presets| presets.import(args.format, &args.text, args.replace))
+}
+
+// 用预设的时长启动倒计时
+#[command]
+async fn launch_preset(
+    timers: tauri::State<'_, TimerHandle>,
+    presets: tauri::State<'_, PresetStore>,
+    args: LaunchPresetArgs,
+) -> Result<TimerSnapshot, String> {
+    let seconds = presets.read(|presets| Ok(presets.get(&args.preset_id)?.seconds))?;
+    timers.start_countdown(args.id, seconds).await
 }
 
 // 删除重复的ping函数定义
 
 // 触发通知
 #[command]
-fn trigger_notification() -> Result<(), String> {
+fn trigger_notification(args: NotificationArgs) -> Result<(), String> {
+    notify(&args.title, &args.body)
+}
+
+// 把文本放进脚本的单引号 / 双引号字符串中
+#[cfg(any(windows, target_os = "macos"))]
+fn quoted(text: &str, quote: char) -> String {
+    let escaped = match quote {
+        '\'' => text.replace('\'', "''"),
+        _ => text.replace('\\', "\\\\").replace('"', "\\\""),
+    };
+    format!("{quote}{escaped}{quote}")
+}
+
+// 发送系统通知，计时器结束和闹钟响铃时也会调用
+pub(crate) fn notify(title: &str, body: &str) -> Result<(), String> {
+    notify_with_sound(title, body, None)
+}
+
+// 发送系统通知并指定声音：为空时使用系统默认声音，"none" 为静音
+// 声音名称由各平台自己解释，例如 macOS 的 Glass、Linux 声音主题中的 bell
+pub(crate) fn notify_with_sound(title: &str, body: &str, sound: Option<&str>) -> Result<(), String> {
     #[cfg(windows)]
     {
         use std::process::Command;
 
+        let sound = match sound {
+            Some("none") => " -Silent".to_string(),
+            Some(sound) => format!(" -Sound {}", quoted(sound, '\'')),
+            None => String::new(),
+        };
         Command::new("powershell")
-            .args(&[
+            .args([
                 "-Command",
-                "New-BurntToastNotification -Title '倒计时结束' -Text '设定的时间已结束！'",
+                &format!(
+                    "New-BurntToastNotification -Title {} -Text {}{}",
+                    quoted(title, '\''),
+                    quoted(body, '\''),
+                    sound
+                ),
             ])
             .status()
             .map_err(|e| format!("Windows 通知失败: {}", e))?;
     }
     #[cfg(target_os = "macos")]
     {
+        let sound = match sound {
+            Some("none") => String::new(),
+            Some(sound) => format!(" sound name {}", quoted(sound, '"')),
+            None => " sound name \"default\"".to_string(),
+        };
         Command::new("osascript")
-            .args(&[
+            .args([
                 "-e",
-                "display notification \"设定的时间已结束！\" with title \"倒计时结束\" sound name \"default\"",
+                &format!(
+                    "display notification {} with title {}{}",
+                    quoted(body, '"'),
+                    quoted(title, '"'),
+                    sound
+                ),
             ])
             .status()
             .map_err(|e| format!("macOS 通知失败: {}", e))?;
@@ -125,20 +406,97 @@ fn trigger_notification() -> Result<(), String> {
     #[cfg(target_os = "linux")]
     {
         Command::new("notify-send")
-            .args(&["倒计时结束", "设定的时间已结束！"])
+            .args(sound_hint(sound))
+            .args([title, body])
             .status()
             .map_err(|e| format!("Linux 通知失败: {}", e))?;
     }
     Ok(())
 }
 
+// notify-send 通过提示（hint）交给通知服务播放声音
+#[cfg(target_os = "linux")]
+fn sound_hint(sound: Option<&str>) -> Option<String> {
+    match sound? {
+        "none" => Some("--hint=boolean:suppress-sound:true".to_string()),
+        sound => Some(format!("--hint=string:sound-name:{}", sound)),
+    }
+}
+
+// 通知上的按钮能否把点击结果交回给应用：只有 Linux 的 notify-send（libnotify 0.7.9 起）可以，
+// macOS 的 osascript 通知和 Windows 的 BurntToast 通知都无法回调，只显示普通通知，界面上会提示用户
+pub(crate) const NOTIFICATION_ACTIONS: bool = cfg!(target_os = "linux");
+
+// 发送带一个按钮的系统通知，阻塞到通知关闭，点击了按钮时返回 true；不支持按钮的系统上发送普通通知
+pub(crate) fn notify_with_action(title: &str, body: &str, action: &str, sound: Option<&str>) -> Result<bool, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = Command::new("notify-send")
+            .args(["--wait", &format!("--action=action={}", action)])
+            .args(sound_hint(sound))
+            .args([title, body])
+            .output()
+            .map_err(|e| format!("Linux 通知失败: {}", e))?;
+        // 不支持 --action 的旧版本会报错退出，此时退回普通通知
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).trim() == "action");
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = action;
+    notify_with_sound(title, body, sound).map(|()| false)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // 使用Builder构建并运行应用，适应Tauri v2的API变化
     Builder::default()
-        .manage(Arc::new(Mutex::new(TimerState::default())))
-        .invoke_handler(tauri::generate_handler![start_timer, pause_timer, reset_timer, set_total_seconds, trigger_notification, ping])
+        .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            // 计时器服务需要 AppHandle 来发送事件，因此在 setup 中启动
+            let timers = timer::spawn(app.handle().clone());
+            app.manage(timers);
+            app.manage(PresetStore::load(app.handle().clone()));
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            create_timer,
+            protocol_version,
+            list_timers,
+            get_timer_state,
+            start_timer,
+            resume_timer,
+            pause_timer,
+            reset_timer,
+            delete_timer,
+            set_total_seconds,
+            set_timer_kind,
+            adjust_timer,
+            snooze_timer,
+            set_finish_options,
+            list_sessions,
+            export_sessions,
+            lap_timer,
+            configure_pomodoro,
+            configure_interval,
+            configure_sequence,
+            run_program,
+            save_alarm,
+            list_alarms,
+            delete_alarm,
+            import_calendar,
+            list_calendars,
+            remove_calendar,
+            list_presets,
+            save_preset,
+            delete_preset,
+            reorder_presets,
+            export_presets,
+            import_presets,
+            launch_preset,
+            trigger_notification,
+            ping
+        ])
         .run(tauri::generate_context!())
         .expect("启动 Tauri 应用失败");
 }
-    
\ No newline at end of file
@@ -0,0 +1,45 @@
+// 倒计时预设：保存在应用数据目录的 presets.json 中，每次修改后立即写入
+
+use std::sync::Mutex;
+
+use beep_engine::preset::{self, Presets};
+use beep_protocol::Preset;
+use tauri::AppHandle;
+
+use crate::storage;
+
+const PRESETS_FILE: &str = "presets.json";
+
+pub struct PresetStore {
+    app: AppHandle,
+    presets: Mutex<Presets>,
+}
+
+impl PresetStore {
+    // 读取保存的预设，第一次启动（文件不存在）时使用默认预设
+    pub fn load(app: AppHandle) -> Self {
+        let items = storage::load_json::<Vec<Preset>>(&app, PRESETS_FILE)
+            .unwrap_or_else(|e| {
+                eprintln!("读取预设失败: {}", e);
+                None
+            })
+            .unwrap_or_else(preset::defaults);
+        Self { app, presets: Mutex::new(Presets::new(items)) }
+    }
+
+    // 只读访问
+    pub fn read<T>(&self, f: impl FnOnce(&Presets) -> Result<T, String>) -> Result<T, String> {
+        let presets = self.presets.lock().map_err(|_| "预设列表不可用".to_string())?;
+        f(&presets)
+    }
+
+    // 在副本上修改预设并写入文件，写入成功后才替换内存中的列表，失败时列表保持不变
+    pub fn update<T>(&self, f: impl FnOnce(&mut Presets) -> Result<T, String>) -> Result<T, String> {
+        let mut presets = self.presets.lock().map_err(|_| "预设列表不可用".to_string())?;
+        let mut updated = presets.clone();
+        let output = f(&mut updated)?;
+        storage::save_json(&self.app, PRESETS_FILE, &updated.list())?;
+        *presets = updated;
+        Ok(output)
+    }
+}
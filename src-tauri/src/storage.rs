@@ -0,0 +1,67 @@
+// 应用数据目录中的 JSON 文件读写，以及只追加的 JSON Lines 记录文件
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+fn data_file(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("无法获取应用数据目录: {}", e))?;
+    Ok(dir.join(name))
+}
+
+// 读取数据文件，文件还不存在时返回空
+pub fn load_json<T: DeserializeOwned>(app: &AppHandle, name: &str) -> Result<Option<T>, String> {
+    let path = data_file(app, name)?;
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("读取 {} 失败: {}", path.display(), e)),
+    };
+    serde_json::from_str(&json).map(Some).map_err(|e| format!("解析 {} 失败: {}", path.display(), e))
+}
+
+// 写入数据文件：先写临时文件再改名，写到一半退出也不会损坏原有数据
+pub fn save_json<T: Serialize>(app: &AppHandle, name: &str, value: &T) -> Result<(), String> {
+    let path = data_file(app, name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建 {} 失败: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, json).map_err(|e| format!("写入 {} 失败: {}", temp.display(), e))?;
+    fs::rename(&temp, &path).map_err(|e| format!("保存 {} 失败: {}", path.display(), e))
+}
+
+// 在记录文件末尾追加若干行，每行一个 JSON 值
+pub fn append_lines<T: Serialize>(app: &AppHandle, name: &str, items: &[T]) -> Result<(), String> {
+    let path = data_file(app, name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建 {} 失败: {}", dir.display(), e))?;
+    }
+    let mut lines = String::new();
+    for item in items {
+        lines.push_str(&serde_json::to_string(item).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开 {} 失败: {}", path.display(), e))?;
+    file.write_all(lines.as_bytes()).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+// 读取记录文件的全部行，文件不存在时为空；写到一半的行和无法解析的行会被跳过
+pub fn load_lines<T: DeserializeOwned>(app: &AppHandle, name: &str) -> Result<Vec<T>, String> {
+    let path = data_file(app, name)?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取 {} 失败: {}", path.display(), e)),
+    };
+    Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
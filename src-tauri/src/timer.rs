@@ -0,0 +1,596 @@
+// 计时器服务：单个 tokio 任务独占计时引擎，通过通道接收命令
+// 所有启动、暂停、删除都在同一个循环里处理，因此不会出现重复的 ticker 或过期的 tick
+// 闹钟也由同一个引擎调度；计时器和闹钟的状态都记录在应用数据目录中，重新启动后据此恢复
+// 导入的日历文件也由这个循环定期检查，文件有变化时重新读取并交给引擎安排提醒
+
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+use beep_engine::{calendar, Clock, SystemClock, TimerEngine};
+use beep_protocol::{
+    Alarm, CalendarSource, FinishOptions, IntervalConfig, Lap, PomodoroConfig, SequenceConfig, TimerEvent, TimerKind,
+    TimerSnapshot, CALENDAR_TIMER_PREFIX, DEFAULT_TIMER_ID, TIMER_EVENT,
+};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::storage;
+
+// 主窗口计时器的初始时长
+const DEFAULT_TIMER_SECONDS: u32 = 60;
+
+// 应用数据目录中保存闹钟的文件
+const ALARMS_FILE: &str = "alarms.json";
+
+// 应用数据目录中记录全部计时器快照的文件，运行中的计时器带有墙上截止时刻
+const TIMERS_FILE: &str = "timers.json";
+
+// 应用数据目录中的计时历史，每次计时结束追加一行
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+// 应用数据目录中保存导入的日历文件列表
+const CALENDARS_FILE: &str = "calendars.json";
+
+// 检查日历文件是否有变化的间隔；按修改时间判断，编辑器先写临时文件再改名的保存方式也能发现
+const CALENDAR_POLL: Duration = Duration::from_secs(5);
+
+type Reply<T> = oneshot::Sender<Result<T, String>>;
+
+// 发送给计时器服务的命令，每个命令都带有回复通道
+enum TimerCommand {
+    Create { id: Option<String>, label: Option<String>, kind: TimerKind, seconds: u32, reply: Reply<TimerSnapshot> },
+    List { reply: Reply<Vec<TimerSnapshot>> },
+    Get { id: String, reply: Reply<TimerSnapshot> },
+    Start { id: String, reply: Reply<()> },
+    Resume { id: String, reply: Reply<()> },
+    Pause { id: String, reply: Reply<()> },
+    Reset { id: String, reply: Reply<()> },
+    Delete { id: String, reply: Reply<()> },
+    SetTotal { id: String, seconds: u32, reply: Reply<()> },
+    SetKind { id: String, kind: TimerKind, reply: Reply<TimerSnapshot> },
+    Lap { id: String, reply: Reply<Lap> },
+    StartCountdown { id: String, seconds: u32, reply: Reply<TimerSnapshot> },
+    Adjust { id: String, delta_seconds: i64, reply: Reply<TimerSnapshot> },
+    Snooze { id: String, reply: Reply<()> },
+    SetFinishOptions { id: String, options: FinishOptions, reply: Reply<TimerSnapshot> },
+    ConfigurePomodoro { id: String, config: PomodoroConfig, reply: Reply<TimerSnapshot> },
+    // 间歇训练设置较大，装箱以免撑大所有命令
+    ConfigureInterval { id: String, config: Box<IntervalConfig>, reply: Reply<TimerSnapshot> },
+    ConfigureSequence { id: String, config: SequenceConfig, reply: Reply<TimerSnapshot> },
+    StartSequence { id: String, config: SequenceConfig, reply: Reply<TimerSnapshot> },
+    SaveAlarm { alarm: Alarm, reply: Reply<Alarm> },
+    ListAlarms { reply: Reply<Vec<Alarm>> },
+    DeleteAlarm { id: String, reply: Reply<()> },
+    ImportCalendar { path: String, lead_minutes: u32, reply: Reply<CalendarSource> },
+    ListCalendars { reply: Reply<Vec<CalendarSource>> },
+    RemoveCalendar { path: String, reply: Reply<()> },
+}
+
+// 计时器服务的句柄，由 Tauri 管理并在各命令间共享
+#[derive(Clone)]
+pub struct TimerHandle {
+    tx: mpsc::UnboundedSender<TimerCommand>,
+}
+
+impl TimerHandle {
+    async fn request<T>(&self, make: impl FnOnce(Reply<T>) -> TimerCommand) -> Result<T, String> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(make(reply)).map_err(|_| "计时器服务已停止".to_string())?;
+        rx.await.map_err(|_| "计时器服务未响应".to_string())?
+    }
+
+    pub async fn create(
+        &self,
+        id: Option<String>,
+        label: Option<String>,
+        kind: TimerKind,
+        seconds: u32,
+    ) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::Create { id, label, kind, seconds, reply }).await
+    }
+
+    pub async fn list(&self) -> Result<Vec<TimerSnapshot>, String> {
+        self.request(|reply| TimerCommand::List { reply }).await
+    }
+
+    pub async fn get(&self, id: String) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::Get { id, reply }).await
+    }
+
+    pub async fn start(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Start { id, reply }).await
+    }
+
+    pub async fn resume(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Resume { id, reply }).await
+    }
+
+    pub async fn pause(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Pause { id, reply }).await
+    }
+
+    pub async fn reset(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Reset { id, reply }).await
+    }
+
+    pub async fn delete(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Delete { id, reply }).await
+    }
+
+    pub async fn set_total(&self, id: String, seconds: u32) -> Result<(), String> {
+        self.request(|reply| TimerCommand::SetTotal { id, seconds, reply }).await
+    }
+
+    pub async fn set_kind(&self, id: String, kind: TimerKind) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::SetKind { id, kind, reply }).await
+    }
+
+    pub async fn lap(&self, id: String) -> Result<Lap, String> {
+        self.request(|reply| TimerCommand::Lap { id, reply }).await
+    }
+
+    pub async fn start_countdown(&self, id: String, seconds: u32) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::StartCountdown { id, seconds, reply }).await
+    }
+
+    pub async fn adjust(&self, id: String, delta_seconds: i64) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::Adjust { id, delta_seconds, reply }).await
+    }
+
+    pub async fn snooze(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::Snooze { id, reply }).await
+    }
+
+    pub async fn set_finish_options(&self, id: String, options: FinishOptions) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::SetFinishOptions { id, options, reply }).await
+    }
+
+    pub async fn configure_pomodoro(&self, id: String, config: PomodoroConfig) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::ConfigurePomodoro { id, config, reply }).await
+    }
+
+    pub async fn configure_interval(&self, id: String, config: IntervalConfig) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::ConfigureInterval { id, config: Box::new(config), reply }).await
+    }
+
+    pub async fn configure_sequence(&self, id: String, config: SequenceConfig) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::ConfigureSequence { id, config, reply }).await
+    }
+
+    pub async fn start_sequence(&self, id: String, config: SequenceConfig) -> Result<TimerSnapshot, String> {
+        self.request(|reply| TimerCommand::StartSequence { id, config, reply }).await
+    }
+
+    pub async fn save_alarm(&self, alarm: Alarm) -> Result<Alarm, String> {
+        self.request(|reply| TimerCommand::SaveAlarm { alarm, reply }).await
+    }
+
+    pub async fn list_alarms(&self) -> Result<Vec<Alarm>, String> {
+        self.request(|reply| TimerCommand::ListAlarms { reply }).await
+    }
+
+    pub async fn delete_alarm(&self, id: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::DeleteAlarm { id, reply }).await
+    }
+
+    pub async fn import_calendar(&self, path: String, lead_minutes: u32) -> Result<CalendarSource, String> {
+        self.request(|reply| TimerCommand::ImportCalendar { path, lead_minutes, reply }).await
+    }
+
+    pub async fn list_calendars(&self) -> Result<Vec<CalendarSource>, String> {
+        self.request(|reply| TimerCommand::ListCalendars { reply }).await
+    }
+
+    pub async fn remove_calendar(&self, path: String) -> Result<(), String> {
+        self.request(|reply| TimerCommand::RemoveCalendar { path, reply }).await
+    }
+}
+
+// 启动计时器服务
+pub fn spawn(app: AppHandle) -> TimerHandle {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut engine = TimerEngine::new(SystemClock::new());
+    let listener_app = app.clone();
+    engine.on_event(move |event| forward_event(&listener_app, event));
+    // 运行中的计时器按截止时刻继续，关闭期间已经到点的记为错过
+    match storage::load_json::<Vec<TimerSnapshot>>(&app, TIMERS_FILE) {
+        Ok(snapshots) => {
+            for snapshot in snapshots.unwrap_or_default() {
+                engine.restore(snapshot);
+            }
+        }
+        Err(e) => eprintln!("读取计时器记录失败: {}", e),
+    }
+    if !engine.contains(DEFAULT_TIMER_ID) {
+        let _ = engine.create(Some(DEFAULT_TIMER_ID.to_string()), None, TimerKind::Countdown, DEFAULT_TIMER_SECONDS);
+    }
+    // 下一次响铃时刻按当前时间重新计算，应用关闭期间错过的闹钟不再补响
+    match storage::load_json::<Vec<Alarm>>(&app, ALARMS_FILE) {
+        Ok(alarms) => {
+            for alarm in alarms.unwrap_or_default() {
+                if let Err(e) = engine.save_alarm(alarm) {
+                    eprintln!("忽略无效的闹钟: {}", e);
+                }
+            }
+        }
+        Err(e) => eprintln!("读取闹钟失败: {}", e),
+    }
+    let saved_alarms = engine.alarms_revision();
+    // 恢复时可能把计时器记为错过，第一轮循环就写回文件
+    let saved_timers = None;
+    // 日历文件在第一轮循环中读取
+    let calendars = match storage::load_json::<Vec<CalendarSource>>(&app, CALENDARS_FILE) {
+        Ok(sources) => sources
+            .unwrap_or_default()
+            .into_iter()
+            .map(|source| CalendarFile { source, modified: None, loaded: false })
+            .collect(),
+        Err(e) => {
+            eprintln!("读取日历列表失败: {}", e);
+            Vec::new()
+        }
+    };
+    let calendars_checked = None;
+    tauri::async_runtime::spawn(
+        TimerActor { app, engine, rx, saved_alarms, saved_timers, calendars, calendars_checked }.run(),
+    );
+    TimerHandle { tx }
+}
+
+// 系统通知上的按钮，点击后对对应的计时器执行的操作
+enum NotifyAction {
+    Snooze,
+    Start,
+}
+
+impl NotifyAction {
+    fn label(&self) -> &'static str {
+        match self {
+            NotifyAction::Snooze => "稍后提醒",
+            NotifyAction::Start => "开始下一步",
+        }
+    }
+}
+
+// 把引擎事件发给前端，计时结束或分段切换时发送系统通知
+// 倒计时到点（包括开始超时）的通知带“稍后提醒”按钮，等待确认的下一步带“开始下一步”按钮
+// 分段自带的提醒（程序文件中的 notify、sound）替换默认的通知正文和声音
+fn forward_event(app: &AppHandle, event: &TimerEvent) {
+    let _ = app.emit_to("main", TIMER_EVENT, event.clone());
+    let (title, body, action, sound) = match event {
+        TimerEvent::Finished { id, label, .. } if id.starts_with(CALENDAR_TIMER_PREFIX) => {
+            ("日程提醒".to_string(), format!("“{}”即将开始", label), Some((id.clone(), NotifyAction::Snooze)), None)
+        }
+        TimerEvent::Finished { segment: Some(segment), alert, .. } => {
+            let body = alert.message.clone().unwrap_or_else(|| "全部步骤已完成".to_string());
+            (format!("{}结束", segment), body, None, alert.sound.clone())
+        }
+        TimerEvent::Finished { id, .. } => {
+            let action = Some((id.clone(), NotifyAction::Snooze));
+            ("倒计时结束".to_string(), "设定的时间已结束！".to_string(), action, None)
+        }
+        TimerEvent::Overtime { id, .. } => {
+            let action = Some((id.clone(), NotifyAction::Snooze));
+            ("倒计时结束".to_string(), "设定的时间已结束，正在记录超时".to_string(), action, None)
+        }
+        TimerEvent::SegmentFinished { id, ended, next, waiting, alert, .. } => {
+            let (body, action) = if *waiting {
+                (format!("接下来：{}，确认后开始", next), Some((id.clone(), NotifyAction::Start)))
+            } else {
+                (format!("接下来：{}", next), None)
+            };
+            let body = alert.message.clone().unwrap_or(body);
+            (format!("{}结束", ended), body, action, alert.sound.clone())
+        }
+        TimerEvent::AlarmFired { label, .. } => ("闹钟".to_string(), label.clone(), None, None),
+        TimerEvent::Missed { label, deadline_unix_ms, .. } => {
+            let time = chrono::DateTime::from_timestamp_millis(*deadline_unix_ms)
+                .map(|time| time.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                .unwrap_or_default();
+            ("错过的倒计时".to_string(), format!("“{}”在应用关闭期间已于 {} 到点", label, time), None, None)
+        }
+        _ => return,
+    };
+    // 系统通知是阻塞的外部进程调用，不能卡住计时器循环
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some((id, action)) = action else {
+            let _ = crate::notify_with_sound(&title, &body, sound.as_deref());
+            return;
+        };
+        // 带按钮的通知会一直阻塞到通知关闭
+        if let Ok(true) = crate::notify_with_action(&title, &body, action.label(), sound.as_deref()) {
+            let Some(timers) = app.try_state::<TimerHandle>() else {
+                return;
+            };
+            let timers = timers.inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let result = match action {
+                    NotifyAction::Snooze => timers.snooze(id).await,
+                    NotifyAction::Start => timers.start(id).await,
+                };
+                if let Err(e) = result {
+                    eprintln!("{}失败: {}", action.label(), e);
+                }
+            });
+        }
+    });
+}
+
+// 导入的日历文件及其上一次读取时的修改时间
+struct CalendarFile {
+    source: CalendarSource,
+    modified: Option<SystemTime>,
+    // 是否已经把这个文件交给过引擎
+    loaded: bool,
+}
+
+impl CalendarFile {
+    // 读取并解析日历文件，返回其中的事件
+    fn read(&mut self) -> Result<Vec<calendar::CalendarEvent>, String> {
+        let path = &self.source.path;
+        let text = fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path, e))?;
+        let parsed = calendar::parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+        self.source.events = parsed.events.len() as u32;
+        self.source.skipped = parsed.skipped;
+        Ok(parsed.events)
+    }
+}
+
+struct TimerActor {
+    app: AppHandle,
+    engine: TimerEngine<SystemClock>,
+    rx: mpsc::UnboundedReceiver<TimerCommand>,
+    // 已经写入文件的闹钟版本
+    saved_alarms: u64,
+    // 已经写入文件的计时器版本，启动后还没有写过时为空
+    saved_timers: Option<u64>,
+    calendars: Vec<CalendarFile>,
+    // 上一次检查日历文件的时刻，启动后还没有检查过时为空
+    calendars_checked: Option<Instant>,
+}
+
+impl TimerActor {
+    async fn run(mut self) {
+        loop {
+            // 命令或到点引起的变化立即写入文件，应用崩溃时最多丢失正在处理的这一个命令
+            self.check_calendars();
+            self.save_timers();
+            self.record_sessions();
+            self.save_alarms();
+            // 每轮重新计算最早的 tick，命令处理后立即生效；导入了日历时至少每隔 CALENDAR_POLL 醒来检查文件
+            let delay = self
+                .engine
+                .next_wakeup()
+                .map(|wakeup| Duration::from_millis(wakeup.saturating_sub(self.engine.clock().now_ms())))
+                .into_iter()
+                .chain((!self.calendars.is_empty()).then_some(CALENDAR_POLL))
+                .min();
+            tokio::select! {
+                command = self.rx.recv() => match command {
+                    Some(command) => self.handle(command),
+                    None => break,
+                },
+                _ = sleep(delay) => self.engine.poll(),
+            }
+        }
+    }
+
+    // 每隔 CALENDAR_POLL 检查一次日历文件的修改时间，有变化时重新读取
+    fn check_calendars(&mut self) {
+        if self.calendars_checked.is_some_and(|checked| checked.elapsed() < CALENDAR_POLL) {
+            return;
+        }
+        self.calendars_checked = Some(Instant::now());
+        let mut changed = false;
+        for index in 0..self.calendars.len() {
+            changed |= self.reload_calendar(index, false);
+        }
+        if changed {
+            self.save_calendars();
+        }
+    }
+
+    // 文件有变化（或 force）时重新读取，返回读取状态是否有变化
+    // 读取失败（例如文件暂时不存在）时保留之前读到的事件；启动后第一次就失败时按没有事件处理，清理上次留下的提醒
+    fn reload_calendar(&mut self, index: usize, force: bool) -> bool {
+        let file = &mut self.calendars[index];
+        let modified = fs::metadata(&file.source.path).and_then(|metadata| metadata.modified()).ok();
+        if !force && file.loaded && modified.is_some() && modified == file.modified {
+            return false;
+        }
+        let before = file.source.clone();
+        let path = file.source.path.clone();
+        let lead_minutes = file.source.lead_minutes;
+        match file.read() {
+            Ok(events) => {
+                file.source.error = None;
+                file.modified = modified;
+                file.loaded = true;
+                self.engine.set_calendar(&path, lead_minutes, events);
+            }
+            Err(e) => {
+                if file.source.error.as_ref() != Some(&e) {
+                    eprintln!("{}", e);
+                }
+                file.source.error = Some(e);
+                file.modified = modified;
+                if !file.loaded {
+                    file.loaded = true;
+                    self.engine.set_calendar(&path, lead_minutes, Vec::new());
+                }
+            }
+        }
+        self.calendars[index].source != before
+    }
+
+    fn save_calendars(&self) {
+        let sources: Vec<&CalendarSource> = self.calendars.iter().map(|file| &file.source).collect();
+        if let Err(e) = storage::save_json(&self.app, CALENDARS_FILE, &sources) {
+            eprintln!("保存日历列表失败: {}", e);
+        }
+    }
+
+    // 导入日历文件；第一次读取就失败时不加入列表，已经导入过的文件按新的提前量重新读取
+    fn import_calendar(&mut self, path: String, lead_minutes: u32) -> Result<CalendarSource, String> {
+        let path = path.trim().to_string();
+        if path.is_empty() {
+            return Err("请填写日历文件的路径".to_string());
+        }
+        let index = match self.calendars.iter().position(|file| file.source.path == path) {
+            Some(index) => index,
+            None => {
+                let source = CalendarSource { path, lead_minutes, events: 0, skipped: 0, error: None };
+                let mut file = CalendarFile { source, modified: None, loaded: false };
+                file.read()?;
+                self.calendars.push(file);
+                self.calendars.len() - 1
+            }
+        };
+        self.calendars[index].source.lead_minutes = lead_minutes;
+        self.reload_calendar(index, true);
+        self.save_calendars();
+        let source = self.calendars[index].source.clone();
+        match &source.error {
+            Some(e) => Err(e.clone()),
+            None => Ok(source),
+        }
+    }
+
+    fn remove_calendar(&mut self, path: &str) -> Result<(), String> {
+        let index = self
+            .calendars
+            .iter()
+            .position(|file| file.source.path == path)
+            .ok_or_else(|| format!("没有导入过这个日历: {}", path))?;
+        self.calendars.remove(index);
+        self.engine.remove_calendar(path);
+        self.save_calendars();
+        Ok(())
+    }
+
+    // 把结束的计时追加到历史文件，放在保存快照之后：两次写入之间崩溃时宁可少记一条，也不重复记录
+    fn record_sessions(&mut self) {
+        let sessions = self.engine.take_sessions();
+        if sessions.is_empty() {
+            return;
+        }
+        if let Err(e) = storage::append_lines(&self.app, HISTORY_FILE, &sessions) {
+            eprintln!("保存计时历史失败: {}", e);
+        }
+    }
+
+    // 计时器有变化（tick 除外）时写入全部快照
+    fn save_timers(&mut self) {
+        let revision = self.engine.timers_revision();
+        if self.saved_timers == Some(revision) {
+            return;
+        }
+        match storage::save_json(&self.app, TIMERS_FILE, &self.engine.list()) {
+            Ok(()) => self.saved_timers = Some(revision),
+            Err(e) => eprintln!("保存计时器记录失败: {}", e),
+        }
+    }
+
+    // 闹钟有变化（包括响铃后更新了下一次响铃时刻）时写入文件
+    fn save_alarms(&mut self) {
+        let revision = self.engine.alarms_revision();
+        if revision == self.saved_alarms {
+            return;
+        }
+        match storage::save_json(&self.app, ALARMS_FILE, &self.engine.alarms()) {
+            Ok(()) => self.saved_alarms = revision,
+            Err(e) => eprintln!("保存闹钟失败: {}", e),
+        }
+    }
+
+    fn handle(&mut self, command: TimerCommand) {
+        let engine = &mut self.engine;
+        // 回复失败只说明调用方已放弃等待，忽略即可
+        match command {
+            TimerCommand::Create { id, label, kind, seconds, reply } => {
+                let _ = reply.send(engine.create(id, label, kind, seconds));
+            }
+            TimerCommand::List { reply } => {
+                let _ = reply.send(Ok(engine.list()));
+            }
+            TimerCommand::Get { id, reply } => {
+                let _ = reply.send(engine.snapshot(&id));
+            }
+            TimerCommand::Start { id, reply } => {
+                let _ = reply.send(engine.start(&id));
+            }
+            TimerCommand::Resume { id, reply } => {
+                let _ = reply.send(engine.resume(&id));
+            }
+            TimerCommand::Pause { id, reply } => {
+                let _ = reply.send(engine.pause(&id));
+            }
+            TimerCommand::Reset { id, reply } => {
+                let _ = reply.send(engine.reset(&id));
+            }
+            TimerCommand::Delete { id, reply } => {
+                let _ = reply.send(engine.remove(&id));
+            }
+            TimerCommand::SetTotal { id, seconds, reply } => {
+                let _ = reply.send(engine.set_total(&id, seconds));
+            }
+            TimerCommand::SetKind { id, kind, reply } => {
+                let _ = reply.send(engine.set_kind(&id, kind));
+            }
+            TimerCommand::Lap { id, reply } => {
+                let _ = reply.send(engine.lap(&id));
+            }
+            TimerCommand::StartCountdown { id, seconds, reply } => {
+                let _ = reply.send(engine.start_countdown(&id, seconds));
+            }
+            TimerCommand::Adjust { id, delta_seconds, reply } => {
+                let _ = reply.send(engine.adjust(&id, delta_seconds));
+            }
+            TimerCommand::Snooze { id, reply } => {
+                let _ = reply.send(engine.snooze(&id));
+            }
+            TimerCommand::SetFinishOptions { id, options, reply } => {
+                let _ = reply.send(engine.set_finish_options(&id, options));
+            }
+            TimerCommand::ConfigurePomodoro { id, config, reply } => {
+                let _ = reply.send(engine.configure_pomodoro(&id, config));
+            }
+            TimerCommand::ConfigureInterval { id, config, reply } => {
+                let _ = reply.send(engine.configure_interval(&id, *config));
+            }
+            TimerCommand::ConfigureSequence { id, config, reply } => {
+                let _ = reply.send(engine.configure_sequence(&id, config));
+            }
+            TimerCommand::StartSequence { id, config, reply } => {
+                let _ = reply.send(engine.start_sequence(&id, config));
+            }
+            TimerCommand::SaveAlarm { alarm, reply } => {
+                let _ = reply.send(engine.save_alarm(alarm));
+            }
+            TimerCommand::ListAlarms { reply } => {
+                let _ = reply.send(Ok(engine.alarms()));
+            }
+            TimerCommand::DeleteAlarm { id, reply } => {
+                let _ = reply.send(engine.delete_alarm(&id));
+            }
+            // 日历命令需要同时改动文件列表和引擎
+            TimerCommand::ImportCalendar { path, lead_minutes, reply } => {
+                let _ = reply.send(self.import_calendar(path, lead_minutes));
+            }
+            TimerCommand::ListCalendars { reply } => {
+                let _ = reply.send(Ok(self.calendars.iter().map(|file| file.source.clone()).collect()));
+            }
+            TimerCommand::RemoveCalendar { path, reply } => {
+                let _ = reply.send(self.remove_calendar(&path));
+            }
+        }
+    }
+}
+
+async fn sleep(delay: Option<Duration>) {
+    match delay {
+        Some(delay) => tokio::time::sleep(delay).await,
+        None => std::future::pending().await,
+    }
+}
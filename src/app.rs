@@ -1,16 +1,18 @@
+use beep_engine::duration::parse_duration;
+use beep_engine::history::{self, Period};
 use leptos::prelude::*;
 use leptos_meta::*;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 // 从模块中导入所需的内容
-use crate::timer_logic::{TimerState, TOTAL_SECONDS};
+use crate::timer_logic::{format_clock, format_duration, format_ms, Alarm, AlarmRepeat, Alert, CalendarSource, FinishOptions, IntervalConfig, IntervalStep, PomodoroConfig, Preset, PresetFormat, SequenceStep, SessionFormat, SessionOutcome, TimerKind, TimerPhase, TimerState, TOTAL_SECONDS};
 
 // 根据目标架构导入不同的模块
 
 // 为WebAssembly环境导入必要的类型
 #[cfg(target_arch = "wasm32")]
-use crate::wasm_specific::setup_timer_event_listener;
+use crate::wasm_specific::{check_protocol_version, setup_state_sync, setup_timer_event_listener};
 
 #[component]
 pub fn App() -> impl IntoView {
@@ -35,19 +37,42 @@ pub fn App() -> impl IntoView {
     let timer_state_clone11 = Arc::clone(&timer_state);
     let timer_state_clone12 = Arc::clone(&timer_state);
     let timer_state_clone13 = Arc::clone(&timer_state);
+    let timer_state_clone14 = Arc::clone(&timer_state);
+    let timer_state_clone15 = Arc::clone(&timer_state);
+    let timer_state_clone16 = Arc::clone(&timer_state);
+    let timer_state_clone17 = Arc::clone(&timer_state);
+    let timer_state_clone18 = Arc::clone(&timer_state);
+    let timer_state_clone19 = Arc::clone(&timer_state);
+    let timer_state_clone20 = Arc::clone(&timer_state);
 
     // 监听后端计时器更新事件 - 仅在WebAssembly环境中
     #[cfg(target_arch = "wasm32")] {
         // 设置计时器事件监听
         setup_timer_event_listener(&timer_state_clone1);
+        // 先确认协议版本，再从后端恢复真实状态，而不是停留在默认的 TOTAL_SECONDS
+        check_protocol_version(&timer_state_clone1);
+        setup_state_sync(&timer_state_clone1);
     }
 
+    let label = timer_state.lock().unwrap().label;
+    let error = timer_state.lock().unwrap().error;
+    let kind = timer_state.lock().unwrap().kind;
+    let elapsed_seconds = timer_state.lock().unwrap().elapsed_seconds;
+    let laps = timer_state.lock().unwrap().laps;
+    let overtime_seconds = timer_state.lock().unwrap().overtime_seconds;
+    let finish = timer_state.lock().unwrap().finish;
+    let ringing_alarm = timer_state.lock().unwrap().ringing_alarm;
+    let set_ringing_alarm = timer_state.lock().unwrap().set_ringing_alarm;
+    let missed_deadline = timer_state.lock().unwrap().missed_deadline;
+    // 信号句柄可以直接复制，供子组件和只读的视图闭包使用
+    let timer_signals = timer_state.lock().unwrap().clone();
+
     // 创建响应式信号
     let (remaining_seconds, set_remaining_seconds) = create_signal(
         timer_state_clone2.lock().unwrap().remaining_seconds.get_untracked()
     );
-    let (is_running, set_is_running) = create_signal(
-        timer_state_clone3.lock().unwrap().is_running.get_untracked()
+    let (phase, set_phase) = create_signal(
+        timer_state_clone3.lock().unwrap().phase.get_untracked()
     );
     let (total_seconds, set_total_seconds) = create_signal(
         timer_state_clone4.lock().unwrap().total_seconds.get_untracked()
@@ -65,8 +90,8 @@ pub fn App() -> impl IntoView {
     create_effect(move |_| {
         if let Ok(ts) = timer_state_clone3.lock() {
             // 使用with方法确保在响应式上下文中获取信号值
-            let running = ts.is_running.with(|s| *s);
-            set_is_running.set(running);
+            let current = ts.phase.with(|p| *p);
+            set_phase.set(current);
         }
     });
 
@@ -81,7 +106,7 @@ pub fn App() -> impl IntoView {
     // 计时器控制函数
     let start_timer = move |_| {
         if let Ok(mut ts) = timer_state_clone8.lock() {
-            ts.start();
+            ts.start_or_resume();
         }
     };
 
@@ -97,10 +122,80 @@ pub fn App() -> impl IntoView {
         }
     };
 
+    // 切换倒计时 / 秒表
+    let use_countdown = move |_| {
+        if let Ok(ts) = timer_state_clone16.lock() {
+            ts.change_kind(TimerKind::Countdown);
+        }
+    };
+
+    let use_stopwatch = move |_| {
+        if let Ok(ts) = timer_state_clone17.lock() {
+            ts.change_kind(TimerKind::Stopwatch);
+        }
+    };
+
+    let use_pomodoro = move |_| {
+        if let Ok(ts) = timer_state_clone18.lock() {
+            ts.change_kind(TimerKind::Pomodoro);
+        }
+    };
+
+    let use_interval = move |_| {
+        if let Ok(ts) = timer_state_clone19.lock() {
+            ts.change_kind(TimerKind::Interval);
+        }
+    };
+
+    let use_sequence = move |_| {
+        if let Ok(ts) = timer_state_clone20.lock() {
+            ts.change_kind(TimerKind::Sequence);
+        }
+    };
+
+    // 秒表记圈
+    let lap_timer = move |_| {
+        if let Ok(ts) = timer_state_clone14.lock() {
+            ts.lap();
+        }
+    };
+
+    // 导出圈速表
+    let export_laps = move |_| {
+        #[cfg(target_arch = "wasm32")]
+        crate::wasm_specific::export_laps(&timer_state_clone15);
+
+        // 只有浏览器环境能下载文件，其他环境什么也不做
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = &timer_state_clone15;
+    };
+
+    let is_stopwatch = move || kind.get() == TimerKind::Stopwatch;
+    let is_pomodoro = move || kind.get() == TimerKind::Pomodoro;
+    let is_interval = move || kind.get() == TimerKind::Interval;
+    let is_sequence = move || kind.get() == TimerKind::Sequence;
+    let is_overtime = move || phase.get() == TimerPhase::Overtime;
+    let segment_status = {
+        let timer_signals = timer_signals.clone();
+        move || {
+            if is_overtime() {
+                "已超时".to_string()
+            } else {
+                timer_signals.segment_status().unwrap_or_default()
+            }
+        }
+    };
+    let segment_color = {
+        let timer_signals = timer_signals.clone();
+        move || timer_signals.segment_color()
+    };
+
     // 更新总时间的函数
+    // 无法解析的输入显示在输入框下方，而不是被忽略
+    let (duration_error, set_duration_error) = signal(None::<String>);
     let update_total_time = move |ev: leptos::ev::Event| {
         #[cfg(target_arch = "wasm32")]
-        crate::wasm_specific::handle_update_total_time(&ev, &timer_state_clone11);
+        set_duration_error.set(crate::wasm_specific::handle_update_total_time(&ev, &timer_state_clone11).err());
         
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -108,6 +203,17 @@ pub fn App() -> impl IntoView {
             if let Ok(mut ts) = timer_state_clone11.lock() {
                 ts.update_total_time(10);
             }
+            set_duration_error.set(None);
+        }
+    };
+
+    // 计时页 / 历史页切换，打开历史页时重新读取记录
+    let (show_history, set_show_history) = signal(false);
+    let open_history = {
+        let timer_state = timer_signals.clone();
+        move |_| {
+            set_show_history.set(true);
+            timer_state.refresh_history(Some(history_since()));
         }
     };
 
@@ -123,77 +229,1449 @@ pub fn App() -> impl IntoView {
     view! {
         <Title text=title />
         <main class="container min-h-screen flex flex-col items-center justify-center bg-gray-50 p-1 pt-0">
-            <div class="relative w-24 h-24 mx-auto">
-                <svg class="absolute inset-0 w-full h-full" viewBox="0 0 40 40">
-                    <defs>
-                        <filter id="textShadow" x="-20%" y="-20%" width="140%" height="140%">
-                            <feDropShadow dx="0.3" dy="0.3" stdDeviation="0.2" flood-color="#000" flood-opacity="0.2"/>
-                        </filter>
-                    </defs>
-
-                    <circle cx="20" cy="20" r="10" fill="none" stroke="#e6e6e6" stroke-width="1.5"/>
-
-                    <circle
-                        cx="20" cy="20" r="10"
-                        fill="none" stroke="#3b82f6" stroke-width="1.5"
-                        stroke-dasharray="628.3185307179587"
-                        stroke-dashoffset={move || stroke_dashoffset().to_string()}
-                        stroke-linecap="round"
-                        transform="rotate(-90 20 20)"
-                        class="transition-all duration-300 ease-in-out"
-                    />
+            <div
+                class="flex items-center gap-3 mb-2 px-4 py-2 rounded bg-amber-100 text-amber-800"
+                class:hidden=move || ringing_alarm.with(|alarm| alarm.is_none())
+            >
+                <span>{move || format!("闹钟：{}", ringing_alarm.get().unwrap_or_default())}</span>
+                <button on:click=move |_| set_ringing_alarm.set(None) class="text-sm hover:underline">
+                    "知道了"
+                </button>
+            </div>
 
-                    <text
-                        x="20.1" y="20.8"
-                        text-anchor="middle"
-                        dominant-baseline="middle"
-                        font-size="3.2"
-                        font-family="monospace"
-                        fill="#3b3b3bff"
-                        filter="url(#textShadow)"
-                        class="font-bold"
-                    >
-                        {move || format!("{}s", remaining_seconds.get())}
-                    </text>
-                </svg>
+            <div
+                class="flex items-center gap-3 mb-2 px-4 py-2 rounded bg-red-100 text-red-800"
+                class:hidden=move || missed_deadline.with(|missed| missed.is_none())
+            >
+                <span>{move || format!("倒计时已在应用关闭期间于 {} 到点", describe_missed(missed_deadline.get()))}</span>
+                <button
+                    on:click={
+                        let timer_state = timer_signals.clone();
+                        move |_| timer_state.dismiss_missed()
+                    }
+                    class="text-sm hover:underline"
+                >
+                    "知道了"
+                </button>
+            </div>
+
+            <div class="flex gap-2 mb-2">
+                <button on:click=move |_| set_show_history.set(false) class=move || mode_button_class(!show_history.get())>
+                    "计时"
+                </button>
+                <button on:click=open_history class=move || mode_button_class(show_history.get())>
+                    "历史"
+                </button>
+            </div>
+
+            <div class:hidden=move || !show_history.get()>
+                <HistoryPage timer_state=timer_signals.clone() />
             </div>
 
-            <div class="flex flex-col items-center gap-6">
-                <div class="flex flex-wrap gap-4 justify-center">
+            <div class="flex flex-col items-center" class:hidden=move || show_history.get()>
+                <div class="flex gap-2 mb-2">
+                    <button
+                        on:click=use_countdown
+                        class=move || mode_button_class(kind.get() == TimerKind::Countdown)
+                    >
+                        "倒计时"
+                    </button>
+                    <button
+                        on:click=use_stopwatch
+                        class=move || mode_button_class(is_stopwatch())
+                    >
+                        "秒表"
+                    </button>
                     <button
-                        on:click=start_timer
-                        disabled=move || is_running.get()
-                        class="px-6 py-3 bg-blue-600 text-white rounded-full hover:bg-blue-700 disabled:bg-gray-400 transition-colors"
+                        on:click=use_pomodoro
+                        class=move || mode_button_class(is_pomodoro())
                     >
-                        "开始"
+                        "番茄钟"
                     </button>
                     <button
-                        on:click=pause_timer
-                        disabled=move || !is_running.get()
-                        class="px-6 py-3 bg-amber-600 text-white rounded-full hover:bg-amber-700 disabled:bg-gray-400 transition-colors"
+                        on:click=use_interval
+                        class=move || mode_button_class(is_interval())
                     >
-                        "暂停"
+                        "间歇训练"
                     </button>
                     <button
-                        on:click=reset_timer
-                        class="px-6 py-3 bg-gray-600 text-white rounded-full hover:bg-gray-700 transition-colors"
+                        on:click=use_sequence
+                        class=move || mode_button_class(is_sequence())
                     >
-                        "重置"
+                        "顺序计时"
                     </button>
                 </div>
 
-                <div class="flex items-center gap-3 p-2 w-full max-w-xs">
-                    <label for="total-time" class="text-gray-700 dark:text-gray-300 text-lg">"总时间(秒):"</label>
-                    <input
-                        id="total-time"
-                        type="number"
-                        value=move || total_seconds.get().to_string()
-                        on:change=update_total_time
-                        min=1
-                        class="w-28 p-2 border border-gray-300 dark:border-gray-600 rounded bg-white dark:bg-gray-800 text-gray-900 dark:text-white text-lg"
-                    />
+                <div class="relative w-24 h-24 mx-auto">
+                    <svg class="absolute inset-0 w-full h-full" viewBox="0 0 40 40">
+                        <defs>
+                            <filter id="textShadow" x="-20%" y="-20%" width="140%" height="140%">
+                                <feDropShadow dx="0.3" dy="0.3" stdDeviation="0.2" flood-color="#000" flood-opacity="0.2"/>
+                            </filter>
+                        </defs>
+
+                        <circle cx="20" cy="20" r="10" fill="none" stroke="#e6e6e6" stroke-width="1.5"/>
+
+                        <circle
+                            cx="20" cy="20" r="10"
+                            fill="none" stroke=segment_color stroke-width="1.5"
+                            stroke-dasharray="628.3185307179587"
+                            stroke-dashoffset={move || stroke_dashoffset().to_string()}
+                            stroke-linecap="round"
+                            transform="rotate(-90 20 20)"
+                            class="transition-all duration-300 ease-in-out"
+                        />
+
+                        <text
+                            x="20.1" y="20.8"
+                            text-anchor="middle"
+                            dominant-baseline="middle"
+                            font-size="3.2"
+                            font-family="monospace"
+                            fill="#3b3b3bff"
+                            filter="url(#textShadow)"
+                            class="font-bold"
+                        >
+                            {move || if is_stopwatch() {
+                                format!("{}s", elapsed_seconds.get())
+                            } else if is_overtime() {
+                                format!("+{}", format_clock(overtime_seconds.get()))
+                            } else {
+                                format!("{}s", remaining_seconds.get())
+                            }}
+                        </text>
+
+                        <text
+                            x="20" y="25"
+                            text-anchor="middle"
+                            dominant-baseline="middle"
+                            font-size="1.6"
+                            fill="#6b7280"
+                        >
+                            {segment_status}
+                        </text>
+                    </svg>
+                </div>
+
+                <div class="flex flex-col items-center gap-6">
+                    <p class="text-gray-600 text-lg">{move || label.get()}</p>
+                    <PresetBar timer_state=timer_signals.clone() />
+                    <div class="flex flex-wrap gap-4 justify-center">
+                        <button
+                            on:click=start_timer
+                            disabled=move || phase.get() == TimerPhase::Running
+                            class="px-6 py-3 bg-blue-600 text-white rounded-full hover:bg-blue-700 disabled:bg-gray-400 transition-colors"
+                        >
+                            {move || if phase.get() == TimerPhase::Paused { "继续" } else { "开始" }}
+                        </button>
+                        <button
+                            on:click=pause_timer
+                            disabled=move || phase.get() != TimerPhase::Running
+                            class="px-6 py-3 bg-amber-600 text-white rounded-full hover:bg-amber-700 disabled:bg-gray-400 transition-colors"
+                        >
+                            "暂停"
+                        </button>
+                        <button
+                            on:click=lap_timer
+                            disabled=move || phase.get() != TimerPhase::Running
+                            class="px-6 py-3 bg-emerald-600 text-white rounded-full hover:bg-emerald-700 disabled:bg-gray-400 transition-colors"
+                            class:hidden=move || !is_stopwatch()
+                        >
+                            "计圈"
+                        </button>
+                        <button
+                            on:click={
+                                let timer_state = timer_signals.clone();
+                                move |_| timer_state.snooze()
+                            }
+                            class="px-6 py-3 bg-purple-600 text-white rounded-full hover:bg-purple-700 transition-colors"
+                            class:hidden=move || !matches!(phase.get(), TimerPhase::Finished | TimerPhase::Overtime)
+                        >
+                            {move || format!("稍后提醒（{}）", format_duration(finish.get().snooze_seconds))}
+                        </button>
+                        <button
+                            on:click=reset_timer
+                            class="px-6 py-3 bg-gray-600 text-white rounded-full hover:bg-gray-700 transition-colors"
+                        >
+                            "重置"
+                        </button>
+                    </div>
+
+                    // 运行中或暂停的倒计时可以直接加减时间
+                    <div
+                        class="flex gap-2"
+                        class:hidden=move || is_stopwatch() || !matches!(phase.get(), TimerPhase::Running | TimerPhase::Paused)
+                    >
+                        {[(60, "+1分"), (5 * 60, "+5分"), (-60, "−1分")].into_iter().map(|(delta, text)| {
+                            let timer_state = timer_signals.clone();
+                            view! {
+                                <button
+                                    on:click=move |_| timer_state.adjust(delta)
+                                    class="px-3 py-1 rounded-full border border-gray-400 text-gray-700 hover:bg-gray-100"
+                                >
+                                    {text}
+                                </button>
+                            }
+                        }).collect_view()}
+                    </div>
+
+                    <p class="text-red-600 text-sm">{move || error.get().unwrap_or_default()}</p>
+
+                    <div class="flex flex-col gap-1 p-2 w-full max-w-xs" class:hidden=move || is_pomodoro() || is_interval() || is_sequence()>
+                        <div class="flex items-center gap-3">
+                            <label for="total-time" class="text-gray-700 dark:text-gray-300 text-lg">
+                                {move || if is_stopwatch() { "目标时间:" } else { "总时间:" }}
+                            </label>
+                            <input
+                                id="total-time"
+                                type="text"
+                                prop:value=move || format_clock(total_seconds.get())
+                                on:change=update_total_time
+                                placeholder="25m、1:30:00、until 17:30"
+                                class="w-36 p-2 border border-gray-300 dark:border-gray-600 rounded bg-white dark:bg-gray-800 text-gray-900 dark:text-white text-lg"
+                                class:border-red-500=move || duration_error.with(|error| error.is_some())
+                            />
+                        </div>
+                        <p class="text-red-600 text-sm" class:hidden=move || duration_error.with(|error| error.is_none())>
+                            {move || duration_error.get().unwrap_or_default()}
+                        </p>
+                    </div>
+
+                    <div class:hidden=is_stopwatch>
+                        <FinishSettings timer_state=timer_signals.clone() />
+                    </div>
+
+                    <div class:hidden=move || !is_pomodoro()>
+                        <PomodoroSettings timer_state=timer_signals.clone() />
+                    </div>
+
+                    <div class:hidden=move || !is_interval()>
+                        <IntervalSettings timer_state=timer_signals.clone() />
+                    </div>
+
+                    <div class:hidden=move || !is_sequence()>
+                        <SequenceSettings timer_state=timer_signals.clone() />
+                    </div>
+
+                    <div
+                        class="w-full max-w-xs"
+                        class:hidden=move || !is_stopwatch() || laps.with(|laps| laps.is_empty())
+                    >
+                        <table class="w-full text-sm text-gray-700 font-mono">
+                            <thead>
+                                <tr class="text-gray-500">
+                                    <th class="text-left">"圈"</th>
+                                    <th class="text-right">"单圈"</th>
+                                    <th class="text-right">"累计"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {move || laps.get().into_iter().rev().map(|lap| view! {
+                                    <tr>
+                                        <td>{lap.index}</td>
+                                        <td class="text-right">{format_ms(lap.lap_ms)}</td>
+                                        <td class="text-right">{format_ms(lap.split_ms)}</td>
+                                    </tr>
+                                }).collect_view()}
+                            </tbody>
+                        </table>
+                        <button
+                            on:click=export_laps
+                            class="mt-2 px-4 py-1 text-sm text-blue-600 hover:underline"
+                        >
+                            "导出圈速"
+                        </button>
+                    </div>
+
+                    <AlarmList timer_state=timer_signals.clone() />
+                    <CalendarList timer_state=timer_signals />
                 </div>
             </div>
         </main>
     }
 }
+
+// 模式切换按钮的样式
+fn mode_button_class(active: bool) -> &'static str {
+    if active {
+        "px-4 py-1 rounded-full bg-blue-600 text-white"
+    } else {
+        "px-4 py-1 rounded-full text-gray-600 hover:bg-gray-200"
+    }
+}
+
+// 到点后的处理：是否继续记录超时，以及稍后提醒的时长
+#[component]
+fn FinishSettings(timer_state: TimerState) -> impl IntoView {
+    let finish = timer_state.finish;
+    let notification_actions = timer_state.notification_actions;
+    let (snooze_error, set_snooze_error) = signal(None::<String>);
+
+    let set_overtime = {
+        let timer_state = timer_state.clone();
+        move |ev: leptos::ev::Event| {
+            let options = FinishOptions { overtime: event_target_checked(&ev), ..finish.get_untracked() };
+            timer_state.set_finish_options(options);
+        }
+    };
+    let set_snooze = move |ev: leptos::ev::Event| {
+        match parse_duration(&event_target_value(&ev), &chrono::Local::now()) {
+            Ok(0) => set_snooze_error.set(Some("稍后提醒的时长必须大于 0".to_string())),
+            Ok(snooze_seconds) => {
+                set_snooze_error.set(None);
+                timer_state.set_finish_options(FinishOptions { snooze_seconds, ..finish.get_untracked() });
+            }
+            Err(e) => set_snooze_error.set(Some(e)),
+        }
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            <label class="flex items-center gap-2 text-gray-700">
+                <input
+                    type="checkbox"
+                    prop:checked=move || finish.with(|finish| finish.overtime)
+                    on:change=set_overtime
+                />
+                "到点后继续记录超时"
+            </label>
+            <label class="flex items-center justify-between gap-3 text-gray-700">
+                "稍后提醒:"
+                <input
+                    type="text"
+                    prop:value=move || format_clock(finish.with(|finish| finish.snooze_seconds))
+                    on:change=set_snooze
+                    placeholder="5m"
+                    class="w-24 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                    class:border-red-500=move || snooze_error.with(|error| error.is_some())
+                />
+            </label>
+            <p class="text-red-600 text-sm" class:hidden=move || snooze_error.with(|error| error.is_none())>
+                {move || snooze_error.get().unwrap_or_default()}
+            </p>
+            <p class="text-gray-500 text-sm" class:hidden=move || notification_actions.get()>
+                "当前系统的通知上没有按钮，到点后请回到应用内点击“稍后提醒”，等待确认的步骤也请在应用内开始"
+            </p>
+        </div>
+    }
+}
+
+// 番茄钟设置：各段时长（分钟）、长休息间隔，以及是否自动开始下一段
+#[component]
+fn PomodoroSettings(timer_state: TimerState) -> impl IntoView {
+    let pomodoro = timer_state.pomodoro;
+    let (config, set_config) = signal(pomodoro.get_untracked());
+    // 后端快照带来的设置同步到表单
+    Effect::new(move |_| set_config.set(pomodoro.get()));
+
+    let minutes_field = move |text: &'static str, get: fn(&PomodoroConfig) -> u32, set: fn(&mut PomodoroConfig, u32)| {
+        view! {
+            <label class="flex items-center justify-between gap-3 text-gray-700">
+                {text}
+                <input
+                    type="number"
+                    min=1
+                    prop:value=move || config.with(|config| get(config) / 60).to_string()
+                    on:change=move |ev| {
+                        if let Ok(minutes) = event_target_value(&ev).parse::<u32>() {
+                            set_config.update(|config| set(config, minutes.max(1) * 60));
+                        }
+                    }
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+            </label>
+        }
+    };
+
+    let apply = move |_| timer_state.configure_pomodoro(config.get_untracked());
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            {minutes_field("专注(分钟):", |c| c.work_seconds, |c, v| c.work_seconds = v)}
+            {minutes_field("短休息(分钟):", |c| c.short_break_seconds, |c, v| c.short_break_seconds = v)}
+            {minutes_field("长休息(分钟):", |c| c.long_break_seconds, |c, v| c.long_break_seconds = v)}
+            <label class="flex items-center justify-between gap-3 text-gray-700">
+                "长休息间隔(个):"
+                <input
+                    type="number"
+                    min=1
+                    prop:value=move || config.with(|config| config.sessions_before_long_break).to_string()
+                    on:change=move |ev| {
+                        if let Ok(sessions) = event_target_value(&ev).parse::<u32>() {
+                            set_config.update(|config| config.sessions_before_long_break = sessions.max(1));
+                        }
+                    }
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+            </label>
+            <label class="flex items-center gap-2 text-gray-700">
+                <input
+                    type="checkbox"
+                    prop:checked=move || config.with(|config| config.auto_start)
+                    on:change=move |ev| set_config.update(|config| config.auto_start = event_target_checked(&ev))
+                />
+                "自动开始下一段"
+            </label>
+            <button
+                on:click=apply
+                class="px-4 py-2 bg-blue-600 text-white rounded-full hover:bg-blue-700 transition-colors"
+            >
+                "应用设置"
+            </button>
+        </div>
+    }
+}
+
+// 间歇训练设置：热身、运动、休息、放松各自的名称、时长（秒）和颜色，以及轮数
+#[component]
+fn IntervalSettings(timer_state: TimerState) -> impl IntoView {
+    let interval = timer_state.interval;
+    let (config, set_config) = signal(interval.get_untracked());
+    // 后端快照带来的设置同步到表单
+    Effect::new(move |_| set_config.set(interval.get()));
+
+    // 一种分段的表单行，get / get_mut 选出配置中对应的分段，min 为允许的最短时长
+    let step_row = move |get: fn(&IntervalConfig) -> &IntervalStep,
+                         get_mut: fn(&mut IntervalConfig) -> &mut IntervalStep,
+                         min: u32| {
+        let read = move |f: fn(&IntervalStep) -> String| move || config.with(|config| f(get(config)));
+        view! {
+            <div class="flex items-center gap-2">
+                <input
+                    type="text"
+                    prop:value=read(|step| step.label.clone())
+                    on:change=move |ev| set_config.update(|config| get_mut(config).label = event_target_value(&ev))
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <input
+                    type="number"
+                    min=min
+                    prop:value=read(|step| step.seconds.to_string())
+                    on:change=move |ev| {
+                        if let Ok(seconds) = event_target_value(&ev).parse::<u32>() {
+                            set_config.update(|config| get_mut(config).seconds = seconds.max(min));
+                        }
+                    }
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <span class="text-gray-500">"秒"</span>
+                <input
+                    type="color"
+                    prop:value=read(|step| step.color.clone())
+                    on:change=move |ev| set_config.update(|config| get_mut(config).color = event_target_value(&ev))
+                    class="w-8 h-8"
+                />
+            </div>
+        }
+    };
+
+    let apply = move |_| timer_state.configure_interval(config.get_untracked());
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            {step_row(|config| &config.warm_up, |config| &mut config.warm_up, 0)}
+            {step_row(|config| &config.work, |config| &mut config.work, 1)}
+            {step_row(|config| &config.rest, |config| &mut config.rest, 0)}
+            {step_row(|config| &config.cool_down, |config| &mut config.cool_down, 0)}
+            <label class="flex items-center justify-between gap-3 text-gray-700">
+                "轮数:"
+                <input
+                    type="number"
+                    min=1
+                    prop:value=move || config.with(|config| config.rounds).to_string()
+                    on:change=move |ev| {
+                        if let Ok(rounds) = event_target_value(&ev).parse::<u32>() {
+                            set_config.update(|config| config.rounds = rounds.max(1));
+                        }
+                    }
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+            </label>
+            <p class="text-gray-500 text-sm">"热身或放松设为 0 秒即跳过"</p>
+            <button
+                on:click=apply
+                class="px-4 py-2 bg-blue-600 text-white rounded-full hover:bg-blue-700 transition-colors"
+            >
+                "应用设置"
+            </button>
+        </div>
+    }
+}
+
+// 顺序计时：上方列出各步骤的进度，下方编辑步骤的名称、时长以及是否等待确认后再开始，
+// 也可以直接运行写好的程序文件（.beep）
+#[component]
+fn SequenceSettings(timer_state: TimerState) -> impl IntoView {
+    let sequence = timer_state.sequence;
+    let plan = timer_state.plan;
+    let phase = timer_state.phase;
+    let (config, set_config) = signal(sequence.get_untracked());
+    let (step_error, set_step_error) = signal(None::<String>);
+    let (program_path, set_program_path) = signal(String::new());
+    // 后端快照带来的设置同步到表单
+    Effect::new(move |_| set_config.set(sequence.get()));
+
+    // 当前步骤高亮，已完成的步骤变灰
+    let progress = move || {
+        plan.with(|plan| {
+            let Some(plan) = plan else {
+                return Vec::new();
+            };
+            let current = plan.index as usize;
+            let finished = phase.get() == TimerPhase::Finished;
+            plan.segments
+                .iter()
+                .enumerate()
+                .map(|(index, segment)| {
+                    let done = index < current || (finished && index == current);
+                    let class = if done {
+                        "text-gray-400 line-through"
+                    } else if index == current {
+                        "text-blue-700 font-bold"
+                    } else {
+                        "text-gray-700"
+                    };
+                    view! {
+                        <li class=format!("flex justify-between {}", class)>
+                            <span>{format!("{}. {}", index + 1, segment.label)}</span>
+                            <span class="font-mono">{format_clock(segment.seconds)}</span>
+                        </li>
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    // 上一步已经结束、这一步还没开始
+    let waiting = move || {
+        phase.get() == TimerPhase::Idle && plan.with(|plan| plan.as_ref().is_some_and(|plan| plan.index > 0))
+    };
+
+    let rows = move || {
+        let count = config.with(|config| config.steps.len());
+        config
+            .get()
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let retime = move |ev| match parse_duration(&event_target_value(&ev), &chrono::Local::now()) {
+                    Ok(0) => set_step_error.set(Some("步骤时长必须大于 0".to_string())),
+                    Ok(seconds) => {
+                        set_step_error.set(None);
+                        set_config.update(|config| config.steps[index].seconds = seconds);
+                    }
+                    Err(e) => set_step_error.set(Some(e)),
+                };
+                view! {
+                    <li class="flex items-center gap-2">
+                        <input
+                            type="text"
+                            prop:value=step.label.clone()
+                            on:change=move |ev| set_config.update(|config| config.steps[index].label = event_target_value(&ev))
+                            class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                        />
+                        <input
+                            type="text"
+                            prop:value=format_clock(step.seconds)
+                            on:change=retime
+                            class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                        />
+                        // 第一步由“开始”按钮启动，不需要等待确认
+                        <label class="flex items-center gap-1 text-sm text-gray-700" class:invisible={index == 0}>
+                            <input
+                                type="checkbox"
+                                prop:checked=step.wait
+                                on:change=move |ev| set_config.update(|config| config.steps[index].wait = event_target_checked(&ev))
+                            />
+                            "等待确认"
+                        </label>
+                        <button
+                            on:click=move |_| set_config.update(|config| {
+                                config.steps.remove(index);
+                            })
+                            disabled={count == 1}
+                            class="text-sm text-red-600 hover:underline disabled:text-gray-300"
+                        >
+                            "删除"
+                        </button>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    let add_step = move |_| {
+        set_config.update(|config| {
+            let label = format!("步骤{}", config.steps.len() + 1);
+            config.steps.push(SequenceStep { label, seconds: 60, wait: false, alert: Alert::default() });
+        })
+    };
+    let run_program = {
+        let timer_state = timer_state.clone();
+        move |_| timer_state.run_program(program_path.get_untracked().trim().to_string())
+    };
+    let apply = move |_| timer_state.configure_sequence(config.get_untracked());
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            <div class="flex items-center gap-2">
+                <input
+                    type="text"
+                    placeholder=".beep 程序文件路径"
+                    prop:value=move || program_path.get()
+                    on:input=move |ev| set_program_path.set(event_target_value(&ev))
+                    class="flex-1 min-w-0 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <button on:click=run_program class="px-3 py-1 bg-blue-600 text-white rounded-full hover:bg-blue-700">
+                    "运行"
+                </button>
+            </div>
+            <p class="text-gray-700" class:hidden=move || sequence.with(|sequence| sequence.name.is_none())>
+                {move || sequence.with(|sequence| sequence.name.clone().unwrap_or_default())}
+            </p>
+            <ol class="text-sm">{progress}</ol>
+            <p class="text-amber-700 text-sm" class:hidden=move || !waiting()>
+                "上一步已结束，点击“开始”进行下一步"
+            </p>
+            <ul class="flex flex-col gap-1">{rows}</ul>
+            <p class="text-red-600 text-sm" class:hidden=move || step_error.with(|error| error.is_none())>
+                {move || step_error.get().unwrap_or_default()}
+            </p>
+            <button on:click=add_step class="self-start text-sm text-blue-600 hover:underline">
+                "添加步骤"
+            </button>
+            <button
+                on:click=apply
+                class="px-4 py-2 bg-blue-600 text-white rounded-full hover:bg-blue-700 transition-colors"
+            >
+                "应用设置"
+            </button>
+        </div>
+    }
+}
+
+// 预设按钮：点击即按预设时长开始倒计时，“管理”展开编辑面板
+#[component]
+fn PresetBar(timer_state: TimerState) -> impl IntoView {
+    let presets = timer_state.presets;
+    let (editing, set_editing) = signal(false);
+
+    let buttons = {
+        let timer_state = timer_state.clone();
+        move || {
+            let timer_state = timer_state.clone();
+            presets
+                .get()
+                .into_iter()
+                .map(|preset| {
+                    let launch = {
+                        let timer_state = timer_state.clone();
+                        let id = preset.id.clone();
+                        move |_| timer_state.launch_preset(id.clone())
+                    };
+                    view! {
+                        <button
+                            on:click=launch
+                            class="px-3 py-1 rounded-full border border-blue-600 text-blue-600 hover:bg-blue-50"
+                        >
+                            {format!("{} {}", preset.name, format_duration(preset.seconds))}
+                        </button>
+                    }
+                })
+                .collect_view()
+        }
+    };
+
+    view! {
+        <div class="flex flex-col items-center gap-2 w-full max-w-md">
+            <div class="flex flex-wrap gap-2 justify-center">
+                {buttons}
+                <button
+                    on:click=move |_| set_editing.update(|editing| *editing = !*editing)
+                    class="px-3 py-1 text-sm text-gray-600 hover:underline"
+                >
+                    {move || if editing.get() { "完成" } else { "管理预设" }}
+                </button>
+            </div>
+            <div class="w-full" class:hidden=move || !editing.get()>
+                <PresetEditor timer_state=timer_state />
+            </div>
+        </div>
+    }
+}
+
+// 预设编辑：修改名称和时长、调整顺序、删除、新建，以及导入导出
+#[component]
+fn PresetEditor(timer_state: TimerState) -> impl IntoView {
+    let presets = timer_state.presets;
+    let (new_name, set_new_name) = signal(String::new());
+    let (new_duration, set_new_duration) = signal("5m".to_string());
+    let (replace_on_import, set_replace_on_import) = signal(false);
+
+    let rows = {
+        let timer_state = timer_state.clone();
+        move || {
+            let timer_state = timer_state.clone();
+            let count = presets.with(|presets| presets.len());
+            presets
+                .get()
+                .into_iter()
+                .enumerate()
+                .map(|(index, preset)| {
+                    let rename = {
+                        let timer_state = timer_state.clone();
+                        let preset = preset.clone();
+                        move |ev| timer_state.save_preset(Preset { name: event_target_value(&ev), ..preset.clone() })
+                    };
+                    let retime = {
+                        let timer_state = timer_state.clone();
+                        let preset = preset.clone();
+                        move |ev| match parse_duration(&event_target_value(&ev), &chrono::Local::now()) {
+                            Ok(seconds) => timer_state.save_preset(Preset { seconds, ..preset.clone() }),
+                            Err(err) => timer_state.set_error.set(Some(err)),
+                        }
+                    };
+                    let move_up = {
+                        let timer_state = timer_state.clone();
+                        let id = preset.id.clone();
+                        move |_| timer_state.move_preset(&id, -1)
+                    };
+                    let move_down = {
+                        let timer_state = timer_state.clone();
+                        let id = preset.id.clone();
+                        move |_| timer_state.move_preset(&id, 1)
+                    };
+                    let delete = {
+                        let timer_state = timer_state.clone();
+                        let id = preset.id.clone();
+                        move |_| timer_state.delete_preset(id.clone())
+                    };
+                    view! {
+                        <li class="flex items-center gap-2 py-1">
+                            <input
+                                type="text"
+                                prop:value=preset.name.clone()
+                                on:change=rename
+                                class="flex-1 min-w-0 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                            />
+                            <input
+                                type="text"
+                                prop:value=format_clock(preset.seconds)
+                                on:change=retime
+                                class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                            />
+                            <button on:click=move_up disabled={index == 0} class="px-1 text-gray-600 disabled:text-gray-300">"↑"</button>
+                            <button on:click=move_down disabled={index + 1 == count} class="px-1 text-gray-600 disabled:text-gray-300">"↓"</button>
+                            <button on:click=delete class="text-sm text-red-600 hover:underline">"删除"</button>
+                        </li>
+                    }
+                })
+                .collect_view()
+        }
+    };
+
+    let add = {
+        let timer_state = timer_state.clone();
+        move |_| match parse_duration(&new_duration.get_untracked(), &chrono::Local::now()) {
+            Ok(seconds) => {
+                timer_state.save_preset(Preset { id: String::new(), name: new_name.get_untracked(), seconds });
+                set_new_name.set(String::new());
+            }
+            Err(err) => timer_state.set_error.set(Some(err)),
+        }
+    };
+
+    let export_json = {
+        let timer_state = timer_state.clone();
+        move |_| export_presets(&timer_state, PresetFormat::Json)
+    };
+    let export_toml = {
+        let timer_state = timer_state.clone();
+        move |_| export_presets(&timer_state, PresetFormat::Toml)
+    };
+    let import = move |ev: leptos::ev::Event| {
+        #[cfg(target_arch = "wasm32")]
+        crate::wasm_specific::import_presets(&ev, &timer_state, replace_on_import.get_untracked());
+
+        // 只有浏览器环境能选择文件，其他环境什么也不做
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = (&ev, &timer_state, replace_on_import);
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 border border-gray-200 rounded">
+            <ul class="divide-y divide-gray-200">{rows}</ul>
+            <div class="flex items-center gap-2">
+                <input
+                    type="text"
+                    placeholder="新预设名称"
+                    prop:value=move || new_name.get()
+                    on:input=move |ev| set_new_name.set(event_target_value(&ev))
+                    class="flex-1 min-w-0 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <input
+                    type="text"
+                    placeholder="时长"
+                    prop:value=move || new_duration.get()
+                    on:input=move |ev| set_new_duration.set(event_target_value(&ev))
+                    class="w-20 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <button on:click=add class="px-3 py-1 bg-blue-600 text-white rounded-full hover:bg-blue-700">"添加"</button>
+            </div>
+            <div class="flex flex-wrap items-center gap-3 text-sm">
+                <button on:click=export_json class="text-blue-600 hover:underline">"导出 JSON"</button>
+                <button on:click=export_toml class="text-blue-600 hover:underline">"导出 TOML"</button>
+                <label class="text-blue-600 hover:underline cursor-pointer">
+                    "导入…"
+                    <input type="file" accept=".json,.toml" on:change=import class="hidden" />
+                </label>
+                <label class="flex items-center gap-1 text-gray-700">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || replace_on_import.get()
+                        on:change=move |ev| set_replace_on_import.set(event_target_checked(&ev))
+                    />
+                    "导入时替换现有预设"
+                </label>
+            </div>
+        </div>
+    }
+}
+
+// 导出预设文件
+fn export_presets(timer_state: &TimerState, format: PresetFormat) {
+    #[cfg(target_arch = "wasm32")]
+    crate::wasm_specific::export_presets(timer_state, format);
+
+    // 只有浏览器环境能下载文件，其他环境什么也不做
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = (timer_state, format);
+}
+
+// 星期的中文名，下标 0 为周一
+const WEEKDAY_NAMES: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+// 闹钟表单，时间和日期保持输入框中的原始文本，提交时再解析
+#[derive(Clone)]
+struct AlarmForm {
+    // 正在编辑的闹钟，新建时为空
+    id: String,
+    label: String,
+    time: String,
+    repeat: String,
+    date: String,
+    weekdays: Vec<u32>,
+    month_day: u32,
+    week: i32,
+    weekday: u32,
+}
+
+impl Default for AlarmForm {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            label: "闹钟".to_string(),
+            time: "07:00".to_string(),
+            repeat: "daily".to_string(),
+            date: String::new(),
+            weekdays: vec![1, 2, 3, 4, 5],
+            month_day: 1,
+            week: 1,
+            weekday: 1,
+        }
+    }
+}
+
+impl AlarmForm {
+    // 把已有闹钟填进表单
+    fn from_alarm(alarm: &Alarm) -> Self {
+        let mut form = Self {
+            id: alarm.id.clone(),
+            label: alarm.label.clone(),
+            time: format!("{:02}:{:02}", alarm.hour, alarm.minute),
+            ..Self::default()
+        };
+        form.repeat = match &alarm.repeat {
+            AlarmRepeat::Once { year, month, day } => {
+                form.date = format!("{:04}-{:02}-{:02}", year, month, day);
+                "once"
+            }
+            AlarmRepeat::Daily => "daily",
+            AlarmRepeat::Weekly { weekdays } => {
+                form.weekdays = weekdays.clone();
+                "weekly"
+            }
+            AlarmRepeat::MonthlyDay { day } => {
+                form.month_day = *day;
+                "monthly_day"
+            }
+            AlarmRepeat::MonthlyWeekday { week, weekday } => {
+                form.week = *week;
+                form.weekday = *weekday;
+                "monthly_weekday"
+            }
+        }
+        .to_string();
+        form
+    }
+
+    // 解析表单，输入有误时返回可以直接显示的错误
+    fn to_alarm(&self, enabled: bool) -> Result<Alarm, String> {
+        let (hour, minute) = self
+            .time
+            .split_once(':')
+            .and_then(|(hour, minute)| Some((hour.parse().ok()?, minute.parse().ok()?)))
+            .ok_or_else(|| "请填写响铃时间".to_string())?;
+        let repeat = match self.repeat.as_str() {
+            "once" => {
+                let mut parts = self.date.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+                match (parts.next().flatten(), parts.next().flatten(), parts.next().flatten()) {
+                    (Some(year), Some(month), Some(day)) => AlarmRepeat::Once { year: year as i32, month, day },
+                    _ => return Err("请选择日期".to_string()),
+                }
+            }
+            "weekly" => AlarmRepeat::Weekly { weekdays: self.weekdays.clone() },
+            "monthly_day" => AlarmRepeat::MonthlyDay { day: self.month_day },
+            "monthly_weekday" => AlarmRepeat::MonthlyWeekday { week: self.week, weekday: self.weekday },
+            _ => AlarmRepeat::Daily,
+        };
+        Ok(Alarm {
+            id: self.id.clone(),
+            label: self.label.trim().to_string(),
+            hour,
+            minute,
+            repeat,
+            enabled,
+            next_fire_unix_ms: None,
+        })
+    }
+}
+
+// 重复规则的文字描述
+fn describe_repeat(repeat: &AlarmRepeat) -> String {
+    match repeat {
+        AlarmRepeat::Once { year, month, day } => format!("{}年{}月{}日", year, month, day),
+        AlarmRepeat::Daily => "每天".to_string(),
+        AlarmRepeat::Weekly { weekdays } if weekdays.as_slice() == [1, 2, 3, 4, 5] => "工作日".to_string(),
+        AlarmRepeat::Weekly { weekdays } => {
+            let names: Vec<&str> = weekdays.iter().filter_map(|day| WEEKDAY_NAMES.get(*day as usize - 1).copied()).collect();
+            format!("每周{}", names.join("、"))
+        }
+        AlarmRepeat::MonthlyDay { day } => format!("每月{}日", day),
+        AlarmRepeat::MonthlyWeekday { week, weekday } => {
+            let week = if *week == -1 { "最后一个".to_string() } else { format!("第{}个", week) };
+            format!("每月{}周{}", week, WEEKDAY_NAMES.get(*weekday as usize - 1).unwrap_or(&""))
+        }
+    }
+}
+
+// 下一次响铃时间，按本地时区显示
+fn describe_next_fire(alarm: &Alarm) -> String {
+    use chrono::{DateTime, Datelike, Local};
+
+    let Some(fire) = alarm.next_fire_unix_ms.and_then(DateTime::from_timestamp_millis) else {
+        return if alarm.enabled { "不会再响".to_string() } else { "已停用".to_string() };
+    };
+    let fire = fire.with_timezone(&Local);
+    let weekday = WEEKDAY_NAMES[fire.weekday().num_days_from_monday() as usize];
+    format!("下次：{} 周{}", fire.format("%m月%d日 %H:%M"), weekday)
+}
+
+// 错过的截止时刻，显示为本地时间
+fn describe_missed(deadline_unix_ms: Option<i64>) -> String {
+    deadline_unix_ms
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|deadline| deadline.with_timezone(&chrono::Local).format("%m月%d日 %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+// 历史页统计的天数和周数（包括今天和本周）
+const HISTORY_DAYS: u64 = 7;
+const HISTORY_WEEKS: u64 = 4;
+// 历史页列出的最近记录条数
+const RECENT_SESSIONS: usize = 20;
+
+// 历史页读取记录的起点：覆盖统计的最近几周，多读的记录不影响统计
+fn history_since() -> i64 {
+    const DAY_MS: i64 = 24 * 3600 * 1000;
+    chrono::Local::now().timestamp_millis() - (HISTORY_WEEKS as i64 * 7 + 1) * DAY_MS
+}
+
+// 把界面上的起止日期（都包含在内）换算成 [开始, 结束) 的 Unix 毫秒，日期无效或顺序颠倒时为空
+fn export_range(from: &str, to: &str) -> Option<(i64, i64)> {
+    use chrono::{Days, Local, NaiveDate};
+
+    let day_start = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest().map(|time| time.timestamp_millis())
+    };
+    let from = NaiveDate::parse_from_str(from, "%Y-%m-%d").ok()?;
+    let to = NaiveDate::parse_from_str(to, "%Y-%m-%d").ok()?.checked_add_days(Days::new(1))?;
+    (from < to).then_some((day_start(from)?, day_start(to)?))
+}
+
+// 完成率，没有记录时显示横线
+fn describe_rate(period: &Period) -> String {
+    period
+        .completion_rate()
+        .map_or_else(|| "—".to_string(), |rate| format!("{:.0}%", rate * 100.0))
+}
+
+fn describe_outcome(outcome: SessionOutcome) -> &'static str {
+    match outcome {
+        SessionOutcome::Completed => "完成",
+        SessionOutcome::Cancelled => "取消",
+        SessionOutcome::Reset => "重置",
+    }
+}
+
+// 一组时间段的汇总表
+fn period_table(title: &'static str, rows: Vec<(String, Period)>) -> impl IntoView {
+    view! {
+        <table class="w-full text-sm text-gray-700">
+            <thead>
+                <tr class="text-gray-500">
+                    <th class="text-left">{title}</th>
+                    <th class="text-right">"计时"</th>
+                    <th class="text-right">"次数"</th>
+                    <th class="text-right">"完成率"</th>
+                </tr>
+            </thead>
+            <tbody>
+                {rows.into_iter().map(|(name, period)| view! {
+                    <tr>
+                        <td>{name}</td>
+                        <td class="text-right">{format_duration((period.actual_ms / 1000) as u32)}</td>
+                        <td class="text-right">{period.sessions}</td>
+                        <td class="text-right">{describe_rate(&period)}</td>
+                    </tr>
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+// 历史页：最近几天和几周的计时总时长、完成率，以及最近的记录
+#[component]
+fn HistoryPage(timer_state: TimerState) -> impl IntoView {
+    use chrono::{DateTime, Datelike, Days, Local};
+
+    let sessions = timer_state.sessions;
+    // 导出的日期范围，两端都包含，默认最近 7 天
+    let today = Local::now().date_naive();
+    let first_day = today.checked_sub_days(Days::new(HISTORY_DAYS - 1)).unwrap_or(today);
+    let (export_from, set_export_from) = signal(first_day.format("%Y-%m-%d").to_string());
+    let (export_to, set_export_to) = signal(today.format("%Y-%m-%d").to_string());
+    let (export_status, set_export_status) = signal(None::<String>);
+    let export = {
+        let timer_state = timer_state.clone();
+        move |format: SessionFormat| {
+            let Some((from, to)) = export_range(&export_from.get_untracked(), &export_to.get_untracked()) else {
+                set_export_status.set(Some("请选择有效的日期范围".to_string()));
+                return;
+            };
+            timer_state.export_sessions(format, from, to, move |path| {
+                set_export_status.set(path.map(|path| format!("已导出到 {}", path)));
+            });
+        }
+    };
+    let export_csv = {
+        let export = export.clone();
+        move |_| export(SessionFormat::Csv)
+    };
+    let export_ics = move |_| export(SessionFormat::Ics);
+    let daily = move || {
+        let periods = sessions.with(|sessions| history::daily(sessions, &Local, Local::now().date_naive(), HISTORY_DAYS));
+        let rows = periods
+            .into_iter()
+            .rev()
+            .map(|period| {
+                let weekday = WEEKDAY_NAMES[period.start.weekday().num_days_from_monday() as usize];
+                (format!("{} 周{}", period.start.format("%m月%d日"), weekday), period)
+            })
+            .collect();
+        period_table("日期", rows)
+    };
+    let weekly = move || {
+        let periods = sessions.with(|sessions| history::weekly(sessions, &Local, Local::now().date_naive(), HISTORY_WEEKS));
+        // 最后一行是最近几周的合计
+        let mut total = Period { start: Local::now().date_naive(), actual_ms: 0, sessions: 0, completed: 0 };
+        for period in &periods {
+            total.actual_ms += period.actual_ms;
+            total.sessions += period.sessions;
+            total.completed += period.completed;
+        }
+        let mut rows: Vec<_> = periods
+            .into_iter()
+            .rev()
+            .map(|period| (format!("{}起", period.start.format("%m月%d日")), period))
+            .collect();
+        rows.push((format!("最近{}周", HISTORY_WEEKS), total));
+        period_table("周", rows)
+    };
+    let recent = move || {
+        sessions
+            .get()
+            .into_iter()
+            .rev()
+            .take(RECENT_SESSIONS)
+            .map(|session| {
+                let started = DateTime::from_timestamp_millis(session.started_unix_ms)
+                    .map(|time| time.with_timezone(&Local).format("%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                view! {
+                    <tr>
+                        <td>{started}</td>
+                        <td>{session.label}</td>
+                        <td class="text-right">{format_duration((session.actual_ms / 1000) as u32)}</td>
+                        <td class="text-right">{format_duration(session.planned_seconds)}</td>
+                        <td class="text-right">{describe_outcome(session.outcome)}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 p-2 w-full max-w-md">
+            <div class="flex justify-end">
+                <button
+                    on:click=move |_| timer_state.refresh_history(Some(history_since()))
+                    class="text-sm text-blue-600 hover:underline"
+                >
+                    "刷新"
+                </button>
+            </div>
+            {daily}
+            {weekly}
+            <div class="flex flex-wrap items-center gap-2 text-sm text-gray-700">
+                <input
+                    type="date"
+                    prop:value=move || export_from.get()
+                    on:change=move |ev| set_export_from.set(event_target_value(&ev))
+                    class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                "至"
+                <input
+                    type="date"
+                    prop:value=move || export_to.get()
+                    on:change=move |ev| set_export_to.set(event_target_value(&ev))
+                    class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <button on:click=export_csv class="text-blue-600 hover:underline">"导出 CSV"</button>
+                <button on:click=export_ics class="text-blue-600 hover:underline">"导出日历"</button>
+            </div>
+            <p class="text-sm text-gray-500" class:hidden=move || export_status.with(Option::is_none)>
+                {move || export_status.get().unwrap_or_default()}
+            </p>
+            <p class="text-gray-500" class:hidden=move || sessions.with(|sessions| !sessions.is_empty())>
+                "还没有计时记录"
+            </p>
+            <table class="w-full text-sm text-gray-700" class:hidden=move || sessions.with(|sessions| sessions.is_empty())>
+                <thead>
+                    <tr class="text-gray-500">
+                        <th class="text-left">"开始"</th>
+                        <th class="text-left">"名称"</th>
+                        <th class="text-right">"实际"</th>
+                        <th class="text-right">"计划"</th>
+                        <th class="text-right">"结果"</th>
+                    </tr>
+                </thead>
+                <tbody>{recent}</tbody>
+            </table>
+        </div>
+    }
+}
+
+// 新导入日历时默认提前几分钟提醒
+const DEFAULT_LEAD_MINUTES: u32 = 5;
+
+// 日历提醒：导入本地 .ics 文件，在每个事件开始前若干分钟响；文件修改后由后端自动重新读取
+#[component]
+fn CalendarList(timer_state: TimerState) -> impl IntoView {
+    let calendars = timer_state.calendars;
+    let (path, set_path) = signal(String::new());
+    let (lead_minutes, set_lead_minutes) = signal(DEFAULT_LEAD_MINUTES.to_string());
+    let (form_error, set_form_error) = signal(None::<String>);
+
+    let import = {
+        let timer_state = timer_state.clone();
+        move |_| match lead_minutes.get_untracked().trim().parse::<u32>() {
+            Ok(minutes) => {
+                timer_state.import_calendar(path.get_untracked(), minutes);
+                set_path.set(String::new());
+                set_form_error.set(None);
+            }
+            Err(_) => set_form_error.set(Some("提前量请填写分钟数".to_string())),
+        }
+    };
+
+    let rows = move || {
+        let timer_state = timer_state.clone();
+        calendars
+            .get()
+            .into_iter()
+            .map(|calendar| {
+                let remove = {
+                    let timer_state = timer_state.clone();
+                    let path = calendar.path.clone();
+                    move |_| timer_state.remove_calendar(path.clone())
+                };
+                // 点击文件名把它填回表单，方便修改提前量
+                let edit = {
+                    let calendar = calendar.clone();
+                    move |_| {
+                        set_path.set(calendar.path.clone());
+                        set_lead_minutes.set(calendar.lead_minutes.to_string());
+                    }
+                };
+                view! {
+                    <li class="flex items-center gap-3 py-1">
+                        <div class="flex-1 min-w-0">
+                            <button on:click=edit class="block w-full text-left text-sm truncate hover:underline" title=calendar.path.clone()>
+                                {calendar.path.clone()}
+                            </button>
+                            <div class="text-xs text-gray-500">{describe_calendar(&calendar)}</div>
+                            <div class="text-xs text-red-600">{calendar.error.clone().unwrap_or_default()}</div>
+                        </div>
+                        <button on:click=remove class="text-sm text-red-600 hover:underline">"移除"</button>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            <h2 class="text-gray-700 text-lg">"日历提醒"</h2>
+            <ul class="divide-y divide-gray-200">{rows}</ul>
+            <input
+                type="text"
+                placeholder=".ics 文件路径"
+                prop:value=move || path.get()
+                on:input=move |ev| set_path.set(event_target_value(&ev))
+                class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+            />
+            <div class="flex items-center gap-2 text-gray-700">
+                "提前"
+                <input
+                    type="number"
+                    min="0"
+                    prop:value=move || lead_minutes.get()
+                    on:input=move |ev| set_lead_minutes.set(event_target_value(&ev))
+                    class="w-16 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                "分钟"
+                <button on:click=import class="ml-auto px-3 py-1 bg-blue-600 text-white rounded-full hover:bg-blue-700">
+                    "导入"
+                </button>
+            </div>
+            <p class="text-red-600 text-sm">{move || form_error.get().unwrap_or_default()}</p>
+        </div>
+    }
+}
+
+// 日历文件的读取结果
+fn describe_calendar(calendar: &CalendarSource) -> String {
+    let mut text = format!("提前 {} 分钟 · {} 个事件", calendar.lead_minutes, calendar.events);
+    if calendar.skipped > 0 {
+        text.push_str(&format!("，跳过 {} 个全天或不支持的事件", calendar.skipped));
+    }
+    text
+}
+
+// 闹钟列表：按墙上时间响铃，可以新建、编辑、启用 / 停用和删除
+#[component]
+fn AlarmList(timer_state: TimerState) -> impl IntoView {
+    let alarms = timer_state.alarms;
+    let (form, set_form) = signal(AlarmForm::default());
+    let (form_error, set_form_error) = signal(None::<String>);
+
+    let submit = {
+        let timer_state = timer_state.clone();
+        move |_| {
+            let current = form.get_untracked();
+            // 编辑时保留原来的启用状态
+            let enabled = alarms.with_untracked(|alarms| {
+                alarms.iter().find(|alarm| alarm.id == current.id).is_none_or(|alarm| alarm.enabled)
+            });
+            match current.to_alarm(enabled) {
+                Ok(alarm) => {
+                    timer_state.save_alarm(alarm);
+                    set_form.set(AlarmForm::default());
+                    set_form_error.set(None);
+                }
+                Err(err) => set_form_error.set(Some(err)),
+            }
+        }
+    };
+
+    // 只显示当前重复规则需要的输入项
+    let hidden_unless = move |repeat: &'static str| move || form.with(|form| form.repeat != repeat);
+
+    let rows = move || {
+        let timer_state = timer_state.clone();
+        alarms
+            .get()
+            .into_iter()
+            .map(|alarm| {
+                let toggle = {
+                    let timer_state = timer_state.clone();
+                    let alarm = alarm.clone();
+                    move |ev| timer_state.set_alarm_enabled(alarm.clone(), event_target_checked(&ev))
+                };
+                let edit = {
+                    let alarm = alarm.clone();
+                    move |_| set_form.set(AlarmForm::from_alarm(&alarm))
+                };
+                let delete = {
+                    let timer_state = timer_state.clone();
+                    let id = alarm.id.clone();
+                    move |_| timer_state.delete_alarm(id.clone())
+                };
+                view! {
+                    <li class="flex items-center gap-3 py-1">
+                        <input type="checkbox" prop:checked=alarm.enabled on:change=toggle />
+                        <div class="flex-1" class:text-gray-400=!alarm.enabled>
+                            <div class="font-mono text-lg">{format!("{:02}:{:02}", alarm.hour, alarm.minute)}</div>
+                            <div class="text-sm">{format!("{} · {}", alarm.label, describe_repeat(&alarm.repeat))}</div>
+                            <div class="text-xs text-gray-500">{describe_next_fire(&alarm)}</div>
+                        </div>
+                        <button on:click=edit class="text-sm text-blue-600 hover:underline">"编辑"</button>
+                        <button on:click=delete class="text-sm text-red-600 hover:underline">"删除"</button>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 w-full max-w-xs">
+            <h2 class="text-gray-700 text-lg">"闹钟"</h2>
+            <ul class="divide-y divide-gray-200">{rows}</ul>
+
+            <div class="flex items-center gap-2">
+                <input
+                    type="text"
+                    placeholder="名称"
+                    prop:value=move || form.with(|form| form.label.clone())
+                    on:change=move |ev| set_form.update(|form| form.label = event_target_value(&ev))
+                    class="flex-1 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                <input
+                    type="time"
+                    prop:value=move || form.with(|form| form.time.clone())
+                    on:change=move |ev| set_form.update(|form| form.time = event_target_value(&ev))
+                    class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+            </div>
+            <select
+                prop:value=move || form.with(|form| form.repeat.clone())
+                on:change=move |ev| set_form.update(|form| form.repeat = event_target_value(&ev))
+                class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+            >
+                <option value="daily">"每天"</option>
+                <option value="weekly">"每周"</option>
+                <option value="monthly_day">"每月某日"</option>
+                <option value="monthly_weekday">"每月第几个星期几"</option>
+                <option value="once">"仅一次"</option>
+            </select>
+
+            <input
+                type="date"
+                class:hidden=hidden_unless("once")
+                prop:value=move || form.with(|form| form.date.clone())
+                on:change=move |ev| set_form.update(|form| form.date = event_target_value(&ev))
+                class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+            />
+            <div class="flex gap-2 text-gray-700" class:hidden=hidden_unless("weekly")>
+                {WEEKDAY_NAMES.iter().enumerate().map(|(index, name)| {
+                    let day = index as u32 + 1;
+                    view! {
+                        <label class="flex flex-col items-center text-sm">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || form.with(|form| form.weekdays.contains(&day))
+                                on:change=move |ev| {
+                                    let checked = event_target_checked(&ev);
+                                    set_form.update(|form| {
+                                        form.weekdays.retain(|other| *other != day);
+                                        if checked {
+                                            form.weekdays.push(day);
+                                            form.weekdays.sort_unstable();
+                                        }
+                                    });
+                                }
+                            />
+                            {*name}
+                        </label>
+                    }
+                }).collect_view()}
+            </div>
+            <label class="flex items-center gap-2 text-gray-700" class:hidden=hidden_unless("monthly_day")>
+                "每月"
+                <input
+                    type="number"
+                    min=1
+                    max=31
+                    prop:value=move || form.with(|form| form.month_day).to_string()
+                    on:change=move |ev| {
+                        if let Ok(day) = event_target_value(&ev).parse::<u32>() {
+                            set_form.update(|form| form.month_day = day.clamp(1, 31));
+                        }
+                    }
+                    class="w-16 p-1 border border-gray-300 rounded bg-white text-gray-900"
+                />
+                "日"
+            </label>
+            <div class="flex items-center gap-2 text-gray-700" class:hidden=hidden_unless("monthly_weekday")>
+                "每月"
+                <select
+                    on:change=move |ev| {
+                        if let Ok(week) = event_target_value(&ev).parse::<i32>() {
+                            set_form.update(|form| form.week = week);
+                        }
+                    }
+                    class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+                >
+                    {[(1, "第一个"), (2, "第二个"), (3, "第三个"), (4, "第四个"), (-1, "最后一个")].into_iter().map(|(week, text)| view! {
+                        <option value=week.to_string() prop:selected=move || form.with(|form| form.week == week)>{text}</option>
+                    }).collect_view()}
+                </select>
+                <select
+                    on:change=move |ev| {
+                        if let Ok(weekday) = event_target_value(&ev).parse::<u32>() {
+                            set_form.update(|form| form.weekday = weekday);
+                        }
+                    }
+                    class="p-1 border border-gray-300 rounded bg-white text-gray-900"
+                >
+                    {WEEKDAY_NAMES.iter().enumerate().map(|(index, name)| {
+                        let day = index as u32 + 1;
+                        view! {
+                            <option value=day.to_string() prop:selected=move || form.with(|form| form.weekday == day)>
+                                {format!("周{}", name)}
+                            </option>
+                        }
+                    }).collect_view()}
+                </select>
+            </div>
+
+            <p class="text-red-600 text-sm">{move || form_error.get().unwrap_or_default()}</p>
+            <div class="flex gap-2">
+                <button
+                    on:click=submit
+                    class="flex-1 px-4 py-2 bg-blue-600 text-white rounded-full hover:bg-blue-700 transition-colors"
+                >
+                    {move || if form.with(|form| form.id.is_empty()) { "添加闹钟" } else { "保存闹钟" }}
+                </button>
+                <button
+                    on:click=move |_| set_form.set(AlarmForm::default())
+                    class="px-4 py-2 text-gray-600 rounded-full hover:bg-gray-200"
+                    class:hidden=move || form.with(|form| form.id.is_empty())
+                >
+                    "取消"
+                </button>
+            </div>
+        </div>
+    }
+}
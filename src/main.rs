@@ -3,6 +3,8 @@ mod app;
 mod tauri_utils;
 mod timer_logic;
 mod wasm_specific;
+#[cfg(target_arch = "wasm32")]
+mod web_backend;
 mod dummy_web_imports;
 
 use app::App; // 导入 App 组件
@@ -1,84 +1,668 @@
-use crate::tauri_utils::call_backend;
+use crate::tauri_utils::call_command;
+use beep_protocol::{
+    AdjustTimer, AdjustTimerArgs, AlarmIdArgs, CalendarPathArgs, ConfigureInterval, ConfigureIntervalArgs, ConfigurePomodoro, ConfigurePomodoroArgs, ConfigureSequence, ConfigureSequenceArgs, DeleteAlarm, DeletePreset, ExportSessions,
+    ExportSessionsArgs, ImportCalendar, ImportCalendarArgs, ImportPresets, ImportPresetsArgs, IpcCommand, LapTimer, LaunchPreset, LaunchPresetArgs, ListAlarms, ListCalendars, ListPresets, ListSessions, ListSessionsArgs, NoArgs, PauseTimer, PresetIdArgs,
+    RemoveCalendar, ReorderPresets, ReorderPresetsArgs, ResetTimer, ResumeTimer, RunProgram, RunProgramArgs, SaveAlarm, SavePreset, SetFinishOptions, SetFinishOptionsArgs,
+    SetTimerKind, SetTimerKindArgs, SetTotalSeconds, SetTotalSecondsArgs, SnoozeTimer, StartTimer, TimerIdArgs,
+};
+use beep_engine::duration::parse_duration;
 use leptos::prelude::*;
 
-// 根据目标架构导入不同的模块
-#[cfg(target_arch = "wasm32")]
-use wasm_bindgen::JsValue;
-
-#[cfg(not(target_arch = "wasm32"))]
-use crate::dummy_web_imports::JsValue;
+// 前后端共用的类型
+pub use beep_protocol::{Alarm, AlarmRepeat, Alert, CalendarSource, FinishOptions, IntervalConfig, IntervalStep, Lap, Plan, PomodoroConfig, Preset, PresetFormat, SequenceConfig, SequenceStep, Session, SessionFormat, SessionOutcome, TimerEvent, TimerKind, TimerPhase, TimerSnapshot};
 
 // 常量定义
 pub const TOTAL_SECONDS: u32 = 60;
+// 主窗口对应的后端计时器
+pub const TIMER_ID: &str = beep_protocol::DEFAULT_TIMER_ID;
+// 没有分段颜色时的圆环颜色
+pub const DEFAULT_RING_COLOR: &str = "#3b82f6";
+// 超时阶段的圆环颜色
+pub const OVERTIME_RING_COLOR: &str = "#ef4444";
 
 // 计时器状态管理
 #[derive(Clone)]
 pub struct TimerState {
     pub remaining_seconds: ReadSignal<u32>,
     pub set_remaining_seconds: WriteSignal<u32>,
-    pub is_running: ReadSignal<bool>,
-    pub set_is_running: WriteSignal<bool>,
+    pub phase: ReadSignal<TimerPhase>,
+    pub set_phase: WriteSignal<TimerPhase>,
     pub total_seconds: ReadSignal<u32>,
     pub set_total_seconds: WriteSignal<u32>,
+    pub kind: ReadSignal<TimerKind>,
+    pub set_kind: WriteSignal<TimerKind>,
+    // 秒表的已用时间
+    pub elapsed_seconds: ReadSignal<u32>,
+    pub set_elapsed_seconds: WriteSignal<u32>,
+    pub laps: ReadSignal<Vec<Lap>>,
+    pub set_laps: WriteSignal<Vec<Lap>>,
+    // 番茄钟等模式的分段计划
+    pub plan: ReadSignal<Option<Plan>>,
+    pub set_plan: WriteSignal<Option<Plan>>,
+    pub pomodoro: ReadSignal<PomodoroConfig>,
+    pub set_pomodoro: WriteSignal<PomodoroConfig>,
+    pub interval: ReadSignal<IntervalConfig>,
+    pub set_interval: WriteSignal<IntervalConfig>,
+    pub sequence: ReadSignal<SequenceConfig>,
+    pub set_sequence: WriteSignal<SequenceConfig>,
+    pub label: ReadSignal<String>,
+    pub set_label: WriteSignal<String>,
+    // 到点后的处理方式，以及超时阶段已经超出的秒数
+    pub finish: ReadSignal<FinishOptions>,
+    pub set_finish: WriteSignal<FinishOptions>,
+    pub overtime_seconds: ReadSignal<u32>,
+    pub set_overtime_seconds: WriteSignal<u32>,
+    // 应用关闭期间错过的截止时刻（Unix 毫秒），以及用户已经确认过的那一个
+    pub missed_deadline: ReadSignal<Option<i64>>,
+    pub set_missed_deadline: WriteSignal<Option<i64>>,
+    dismissed_missed: StoredValue<Option<i64>>,
+    // 所有闹钟，以及最近一次响铃、尚未关闭提示的闹钟名
+    pub alarms: ReadSignal<Vec<Alarm>>,
+    pub set_alarms: WriteSignal<Vec<Alarm>>,
+    pub ringing_alarm: ReadSignal<Option<String>>,
+    pub set_ringing_alarm: WriteSignal<Option<String>>,
+    // 导入的日历文件
+    pub calendars: ReadSignal<Vec<CalendarSource>>,
+    pub set_calendars: WriteSignal<Vec<CalendarSource>>,
+    // 倒计时预设，按显示顺序排列
+    pub presets: ReadSignal<Vec<Preset>>,
+    pub set_presets: WriteSignal<Vec<Preset>>,
+    // 计时历史，打开历史页时读取
+    pub sessions: ReadSignal<Vec<Session>>,
+    pub set_sessions: WriteSignal<Vec<Session>>,
+    // 系统通知上能否直接点击“稍后提醒”等按钮，版本握手时由后端告知
+    pub notification_actions: ReadSignal<bool>,
+    pub set_notification_actions: WriteSignal<bool>,
+    // 最近一次后端调用的错误信息
+    pub error: ReadSignal<Option<String>>,
+    pub set_error: WriteSignal<Option<String>>,
 }
 
 impl TimerState {
     // 创建新的计时器状态
     pub fn new() -> Self {
         let (remaining_seconds, set_remaining_seconds) = signal(TOTAL_SECONDS);
-        let (is_running, set_is_running) = signal(false);
+        let (phase, set_phase) = signal(TimerPhase::Idle);
         let (total_seconds, set_total_seconds) = signal(TOTAL_SECONDS);
+        let (kind, set_kind) = signal(TimerKind::Countdown);
+        let (elapsed_seconds, set_elapsed_seconds) = signal(0);
+        let (laps, set_laps) = signal(Vec::new());
+        let (plan, set_plan) = signal(None);
+        let (pomodoro, set_pomodoro) = signal(PomodoroConfig::default());
+        let (interval, set_interval) = signal(IntervalConfig::default());
+        let (sequence, set_sequence) = signal(SequenceConfig::default());
+        let (label, set_label) = signal(String::new());
+        let (finish, set_finish) = signal(FinishOptions::default());
+        let (overtime_seconds, set_overtime_seconds) = signal(0);
+        let (missed_deadline, set_missed_deadline) = signal(None);
+        let dismissed_missed = StoredValue::new(None);
+        let (alarms, set_alarms) = signal(Vec::new());
+        let (ringing_alarm, set_ringing_alarm) = signal(None);
+        let (calendars, set_calendars) = signal(Vec::new());
+        let (presets, set_presets) = signal(Vec::new());
+        let (sessions, set_sessions) = signal(Vec::new());
+        // 握手完成前不显示提示
+        let (notification_actions, set_notification_actions) = signal(true);
+        let (error, set_error) = signal(None);
 
         Self {
             remaining_seconds,
             set_remaining_seconds,
-            is_running,
-            set_is_running,
+            phase,
+            set_phase,
             total_seconds,
             set_total_seconds,
+            kind,
+            set_kind,
+            elapsed_seconds,
+            set_elapsed_seconds,
+            laps,
+            set_laps,
+            plan,
+            set_plan,
+            pomodoro,
+            set_pomodoro,
+            interval,
+            set_interval,
+            sequence,
+            set_sequence,
+            label,
+            set_label,
+            finish,
+            set_finish,
+            overtime_seconds,
+            set_overtime_seconds,
+            missed_deadline,
+            set_missed_deadline,
+            dismissed_missed,
+            alarms,
+            set_alarms,
+            ringing_alarm,
+            set_ringing_alarm,
+            calendars,
+            set_calendars,
+            presets,
+            set_presets,
+            sessions,
+            set_sessions,
+            notification_actions,
+            set_notification_actions,
+            error,
+            set_error,
         }
     }
 
+    // 调用后端命令，成功时清除错误，失败时显示错误
+    fn invoke<C: IpcCommand>(&self, args: C::Args) {
+        let set_error = self.set_error;
+        call_command::<C>(&args, move |result| {
+            set_error.set(result.err());
+        });
+    }
+
+    // 切换阶段，非法转换被忽略并记录日志
+    fn transition(&self, next: TimerPhase) -> bool {
+        let current = self.phase.get_untracked();
+        if !current.can_transition_to(next) {
+            log::warn!("忽略非法的状态转换: {:?} -> {:?}", current, next);
+            return false;
+        }
+        self.set_phase.set(next);
+        true
+    }
+
     // 开始计时器
     pub fn start(&self) {
-        self.set_is_running.set(true);
-        call_backend(
-            "start_timer".to_string(),
-            JsValue::from(self.total_seconds.get_untracked())
-        );
+        let finished = matches!(self.phase.get_untracked(), TimerPhase::Finished | TimerPhase::Overtime);
+        if self.phase.get_untracked() == TimerPhase::Running || !self.transition(TimerPhase::Running) {
+            return;
+        }
+        // 走完的分段计划从第一段重新开始
+        if finished {
+            self.rewind_plan();
+        }
+        let total = self.total_seconds.get_untracked();
+        self.set_remaining_seconds.set(total);
+        self.set_elapsed_seconds.set(0);
+        self.set_laps.set(Vec::new());
+        self.invoke::<StartTimer>(TimerIdArgs::new(TIMER_ID));
+    }
+
+    // 从暂停处继续，保留剩余时间
+    pub fn resume(&self) {
+        if self.phase.get_untracked() != TimerPhase::Paused || !self.transition(TimerPhase::Running) {
+            return;
+        }
+        self.invoke::<ResumeTimer>(TimerIdArgs::new(TIMER_ID));
+    }
+
+    // 暂停时按下开始按钮应继续而不是重新开始
+    pub fn start_or_resume(&self) {
+        if self.phase.get_untracked() == TimerPhase::Paused {
+            self.resume();
+        } else {
+            self.start();
+        }
     }
 
     // 暂停计时器
     pub fn pause(&self) {
-        self.set_is_running.set(false);
-        call_backend("pause_timer".to_string(), JsValue::UNDEFINED);
+        if self.transition(TimerPhase::Paused) {
+            self.invoke::<PauseTimer>(TimerIdArgs::new(TIMER_ID));
+        }
     }
 
     // 重置计时器
     pub fn reset(&self) {
         let total = self.total_seconds.get_untracked();
         self.set_remaining_seconds.set(total);
-        self.set_is_running.set(false);
-        call_backend("reset_timer".to_string(), JsValue::UNDEFINED);
+        self.set_elapsed_seconds.set(0);
+        self.set_laps.set(Vec::new());
+        self.transition(TimerPhase::Idle);
+        self.invoke::<ResetTimer>(TimerIdArgs::new(TIMER_ID));
     }
 
-    // 更新总时间
+    // 更新总时间，秒表模式下是目标时间，不影响计时
     pub fn update_total_time(&self, new_total: u32) {
         self.set_total_seconds.set(new_total);
-        self.set_remaining_seconds.set(new_total);
-        call_backend("set_total_seconds".to_string(), JsValue::from(new_total));
+        if self.kind.get_untracked() == TimerKind::Countdown {
+            self.set_remaining_seconds.set(new_total);
+            if self.phase.get_untracked() != TimerPhase::Running {
+                self.transition(TimerPhase::Idle);
+            }
+        }
+        self.invoke::<SetTotalSeconds>(SetTotalSecondsArgs { id: TIMER_ID.to_string(), seconds: new_total });
+    }
+
+    // 按输入框中的文字设置总时间，支持 25m、1:30:00、until 17:30 等写法
+    // 无法解析时返回错误，由界面显示在输入框旁
+    pub fn set_total_from_input(&self, text: &str) -> Result<(), String> {
+        let seconds = parse_duration(text, &chrono::Local::now())?;
+        // 秒表的目标时间可以为 0，表示没有目标
+        if seconds == 0 && self.kind.get_untracked() != TimerKind::Stopwatch {
+            return Err("倒计时时长必须大于 0".to_string());
+        }
+        self.update_total_time(seconds);
+        Ok(())
+    }
+
+    // 调用返回快照的命令，用返回的快照刷新界面
+    fn invoke_for_snapshot<C: IpcCommand<Output = TimerSnapshot>>(&self, args: C::Args) {
+        let state = self.clone();
+        call_command::<C>(&args, move |result| match result {
+            Ok(snapshot) => {
+                state.set_error.set(None);
+                state.apply_snapshot(snapshot);
+            }
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 给倒计时加减时间，运行中和暂停时都不会重新开始
+    pub fn adjust(&self, delta_seconds: i64) {
+        self.invoke_for_snapshot::<AdjustTimer>(AdjustTimerArgs { id: TIMER_ID.to_string(), delta_seconds });
+    }
+
+    // 已结束或超时中的倒计时稍后提醒，重新开始由后端的 Started 事件同步
+    pub fn snooze(&self) {
+        if matches!(self.phase.get_untracked(), TimerPhase::Finished | TimerPhase::Overtime) {
+            self.invoke::<SnoozeTimer>(TimerIdArgs::new(TIMER_ID));
+        }
+    }
+
+    // 设置到点后是否继续超时计时，以及稍后提醒的时长
+    pub fn set_finish_options(&self, options: FinishOptions) {
+        self.set_finish.set(options.clone());
+        self.invoke_for_snapshot::<SetFinishOptions>(SetFinishOptionsArgs { id: TIMER_ID.to_string(), options });
+    }
+
+    // 关闭错过提示；后端在重新开始或重置前仍会报告同一个截止时刻，同步快照时不再显示
+    pub fn dismiss_missed(&self) {
+        self.dismissed_missed.set_value(self.missed_deadline.get_untracked());
+        self.set_missed_deadline.set(None);
+    }
+
+    // 切换计时方式
+    pub fn change_kind(&self, kind: TimerKind) {
+        if self.kind.get_untracked() == kind {
+            return;
+        }
+        self.invoke_for_snapshot::<SetTimerKind>(SetTimerKindArgs { id: TIMER_ID.to_string(), kind });
+    }
+
+    // 应用番茄钟设置，从第一段专注重新开始
+    pub fn configure_pomodoro(&self, config: PomodoroConfig) {
+        self.set_pomodoro.set(config.clone());
+        self.invoke_for_snapshot::<ConfigurePomodoro>(ConfigurePomodoroArgs { id: TIMER_ID.to_string(), config });
+    }
+
+    // 应用间歇训练设置，从第一段重新开始
+    pub fn configure_interval(&self, config: IntervalConfig) {
+        self.set_interval.set(config.clone());
+        self.invoke_for_snapshot::<ConfigureInterval>(ConfigureIntervalArgs { id: TIMER_ID.to_string(), config });
+    }
+
+    // 应用顺序计时设置，从第一步重新开始
+    pub fn configure_sequence(&self, config: SequenceConfig) {
+        self.set_sequence.set(config.clone());
+        self.invoke_for_snapshot::<ConfigureSequence>(ConfigureSequenceArgs { id: TIMER_ID.to_string(), config });
+    }
+
+    // 载入程序文件（.beep）并立即开始，设置随快照一起回来
+    pub fn run_program(&self, path: String) {
+        self.invoke_for_snapshot::<RunProgram>(RunProgramArgs { id: TIMER_ID.to_string(), path });
+    }
+
+    // 分段计划回到第一段，与后端的重置保持一致
+    fn rewind_plan(&self) {
+        let mut first_seconds = None;
+        self.set_plan.update(|plan| {
+            if let Some(plan) = plan {
+                plan.index = 0;
+                plan.completed_sessions = 0;
+                first_seconds = plan.current().map(|segment| segment.seconds);
+            }
+        });
+        if let Some(seconds) = first_seconds {
+            self.set_total_seconds.set(seconds);
+        }
+    }
+
+    // 圆环下方的分段状态：番茄钟显示已完成的专注数，间歇训练显示当前轮次，顺序计时显示当前步骤
+    pub fn segment_status(&self) -> Option<String> {
+        let kind = self.kind.get();
+        self.plan.with(|plan| {
+            let plan = plan.as_ref()?;
+            let segment = plan.current()?;
+            Some(match (kind, segment.round) {
+                (TimerKind::Interval, Some(round)) => {
+                    format!("{} · 第{}/{}轮", segment.label, round, plan.total_rounds())
+                }
+                (TimerKind::Interval, None) => segment.label.clone(),
+                (TimerKind::Sequence, _) => {
+                    format!("{} · 第{}/{}步", segment.label, plan.index + 1, plan.segments.len())
+                }
+                _ => format!("{} · 已完成{}", segment.label, plan.completed_sessions),
+            })
+        })
+    }
+
+    // 圆环颜色，取当前分段的颜色，超时时为红色
+    pub fn segment_color(&self) -> String {
+        if self.phase.get() == TimerPhase::Overtime {
+            return OVERTIME_RING_COLOR.to_string();
+        }
+        self.plan
+            .with(|plan| plan.as_ref()?.current()?.color.clone())
+            .unwrap_or_else(|| DEFAULT_RING_COLOR.to_string())
+    }
+
+    // 秒表记一圈，新的一圈通过事件加入圈速表
+    pub fn lap(&self) {
+        if self.kind.get_untracked() == TimerKind::Stopwatch && self.phase.get_untracked() == TimerPhase::Running {
+            self.invoke::<LapTimer>(TimerIdArgs::new(TIMER_ID));
+        }
     }
 
-    // 计算圆环进度
+    // 从后端重新读取闹钟列表（包括下一次响铃时间）
+    pub fn refresh_alarms(&self) {
+        let state = self.clone();
+        call_command::<ListAlarms>(&NoArgs {}, move |result| match result {
+            Ok(alarms) => state.set_alarms.set(alarms),
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 从后端重新读取导入的日历文件及其读取结果
+    pub fn refresh_calendars(&self) {
+        let state = self.clone();
+        call_command::<ListCalendars>(&NoArgs {}, move |result| match result {
+            Ok(calendars) => state.set_calendars.set(calendars),
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 导入日历文件，或修改已导入文件的提前量
+    pub fn import_calendar(&self, path: String, lead_minutes: u32) {
+        let state = self.clone();
+        call_command::<ImportCalendar>(&ImportCalendarArgs { path, lead_minutes }, move |result| {
+            match result {
+                Ok(_) => state.set_error.set(None),
+                Err(err) => state.set_error.set(Some(err)),
+            }
+            state.refresh_calendars();
+        });
+    }
+
+    pub fn remove_calendar(&self, path: String) {
+        let state = self.clone();
+        call_command::<RemoveCalendar>(&CalendarPathArgs { path }, move |result| match result {
+            Ok(()) => {
+                state.set_error.set(None);
+                state.refresh_calendars();
+            }
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 新建或更新闹钟，成功后刷新列表
+    pub fn save_alarm(&self, alarm: Alarm) {
+        let state = self.clone();
+        call_command::<SaveAlarm>(&alarm, move |result| match result {
+            Ok(_) => {
+                state.set_error.set(None);
+                state.refresh_alarms();
+            }
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 启用或停用闹钟
+    pub fn set_alarm_enabled(&self, mut alarm: Alarm, enabled: bool) {
+        alarm.enabled = enabled;
+        self.save_alarm(alarm);
+    }
+
+    pub fn delete_alarm(&self, id: String) {
+        let state = self.clone();
+        call_command::<DeleteAlarm>(&AlarmIdArgs { id }, move |result| match result {
+            Ok(()) => {
+                state.set_error.set(None);
+                state.refresh_alarms();
+            }
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 从后端重新读取预设列表
+    pub fn refresh_presets(&self) {
+        self.invoke_for_presets::<ListPresets>(NoArgs {});
+    }
+
+    // 调用返回完整预设列表的命令，用返回值刷新界面
+    fn invoke_for_presets<C: IpcCommand<Output = Vec<Preset>>>(&self, args: C::Args) {
+        let state = self.clone();
+        call_command::<C>(&args, move |result| match result {
+            Ok(presets) => {
+                state.set_error.set(None);
+                state.set_presets.set(presets);
+            }
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 一键启动：切换为倒计时并按预设时长开始
+    pub fn launch_preset(&self, preset_id: String) {
+        self.invoke_for_snapshot::<LaunchPreset>(LaunchPresetArgs { id: TIMER_ID.to_string(), preset_id });
+    }
+
+    // 新建或更新预设，成功后刷新列表
+    pub fn save_preset(&self, preset: Preset) {
+        let state = self.clone();
+        call_command::<SavePreset>(&preset, move |result| match result {
+            Ok(_) => state.refresh_presets(),
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    pub fn delete_preset(&self, id: String) {
+        let state = self.clone();
+        call_command::<DeletePreset>(&PresetIdArgs { id }, move |result| match result {
+            Ok(()) => state.refresh_presets(),
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 把预设向前（offset 为负）或向后移动一位
+    pub fn move_preset(&self, id: &str, offset: isize) {
+        let mut ids: Vec<String> = self.presets.with_untracked(|presets| presets.iter().map(|preset| preset.id.clone()).collect());
+        let Some(index) = ids.iter().position(|other| other == id) else {
+            return;
+        };
+        let Some(target) = index.checked_add_signed(offset).filter(|target| *target < ids.len()) else {
+            return;
+        };
+        ids.swap(index, target);
+        self.invoke_for_presets::<ReorderPresets>(ReorderPresetsArgs { ids });
+    }
+
+    // 导入预设文件的内容
+    pub fn import_presets(&self, format: PresetFormat, text: String, replace: bool) {
+        self.invoke_for_presets::<ImportPresets>(ImportPresetsArgs { format, text, replace });
+    }
+
+    // 从后端读取 since_unix_ms 之后结束的计时历史
+    pub fn refresh_history(&self, since_unix_ms: Option<i64>) {
+        let state = self.clone();
+        call_command::<ListSessions>(&ListSessionsArgs { since_unix_ms }, move |result| match result {
+            Ok(sessions) => state.set_sessions.set(sessions),
+            Err(err) => state.set_error.set(Some(err)),
+        });
+    }
+
+    // 导出 [from_unix_ms, to_unix_ms) 内开始的计时历史，完成后回报保存的位置，取消保存时为空
+    pub fn export_sessions(&self, format: SessionFormat, from_unix_ms: i64, to_unix_ms: i64, done: impl FnOnce(Option<String>) + 'static) {
+        let set_error = self.set_error;
+        call_command::<ExportSessions>(&ExportSessionsArgs { format, from_unix_ms, to_unix_ms }, move |result| match result {
+            Ok(path) => done(path),
+            Err(err) => set_error.set(Some(format!("导出历史失败: {}", err))),
+        });
+    }
+
+    // 圈速表导出为 CSV
+    pub fn laps_csv(&self) -> String {
+        let mut csv = String::from("圈数,单圈(秒),累计(秒)\n");
+        for lap in self.laps.get_untracked() {
+            csv.push_str(&format!("{},{},{}\n", lap.index, format_ms(lap.lap_ms), format_ms(lap.split_ms)));
+        }
+        csv
+    }
+
+    // 应用后端发来的事件，后端已校验过阶段转换，这里直接同步
+    pub fn apply_event(&self, event: TimerEvent) {
+        // 闹钟不属于任何计时器：显示提示，并刷新列表中的下一次响铃时间
+        if let TimerEvent::AlarmFired { label, .. } = event {
+            self.set_ringing_alarm.set(Some(label));
+            self.refresh_alarms();
+            return;
+        }
+        // 后端可能同时运行多个计时器，只处理主窗口对应的那个
+        if event.id() != TIMER_ID {
+            return;
+        }
+        match event {
+            TimerEvent::Started { remaining_seconds, total_seconds, .. } => {
+                self.set_total_seconds.set(total_seconds);
+                self.set_remaining_seconds.set(remaining_seconds);
+                self.set_elapsed_seconds.set(0);
+                self.set_overtime_seconds.set(0);
+                self.set_laps.set(Vec::new());
+                self.set_phase.set(TimerPhase::Running);
+            }
+            TimerEvent::Paused { remaining_seconds, elapsed_seconds, .. } => {
+                self.set_remaining_seconds.set(remaining_seconds);
+                self.set_elapsed_seconds.set(elapsed_seconds);
+                self.set_phase.set(TimerPhase::Paused);
+            }
+            TimerEvent::Resumed { remaining_seconds, .. } => {
+                self.set_remaining_seconds.set(remaining_seconds);
+                self.set_phase.set(TimerPhase::Running);
+            }
+            TimerEvent::Tick { remaining_seconds, elapsed_seconds, overtime_seconds, .. } => {
+                self.set_remaining_seconds.set(remaining_seconds);
+                self.set_elapsed_seconds.set(elapsed_seconds);
+                self.set_overtime_seconds.set(overtime_seconds);
+            }
+            TimerEvent::Finished { .. } => {
+                self.set_remaining_seconds.set(0);
+                self.set_phase.set(TimerPhase::Finished);
+            }
+            TimerEvent::Overtime { .. } => {
+                self.set_remaining_seconds.set(0);
+                self.set_overtime_seconds.set(0);
+                self.set_phase.set(TimerPhase::Overtime);
+            }
+            // 切换到下一段，自动开始时随后还会收到 Started
+            TimerEvent::SegmentFinished { segment_index, completed_sessions, total_seconds, .. } => {
+                self.set_plan.update(|plan| {
+                    if let Some(plan) = plan {
+                        plan.index = segment_index;
+                        plan.completed_sessions = completed_sessions;
+                    }
+                });
+                self.set_total_seconds.set(total_seconds);
+                self.set_remaining_seconds.set(total_seconds);
+                self.set_phase.set(TimerPhase::Idle);
+            }
+            TimerEvent::Reset { remaining_seconds, .. } => {
+                self.set_remaining_seconds.set(remaining_seconds);
+                self.set_elapsed_seconds.set(0);
+                self.set_overtime_seconds.set(0);
+                self.set_laps.set(Vec::new());
+                // 分段计划重置后回到第一段
+                self.rewind_plan();
+                self.set_phase.set(TimerPhase::Idle);
+            }
+            TimerEvent::Lap { lap, .. } => {
+                self.set_laps.update(|laps| laps.push(lap));
+            }
+            TimerEvent::Adjusted { remaining_seconds, total_seconds, .. } => {
+                self.set_total_seconds.set(total_seconds);
+                self.set_remaining_seconds.set(remaining_seconds);
+            }
+            TimerEvent::Missed { deadline_unix_ms, .. } => {
+                self.set_missed_deadline.set(Some(deadline_unix_ms));
+            }
+            TimerEvent::AlarmFired { .. } => {}
+        }
+    }
+
+    // 用后端快照覆盖本地状态，保证界面与后端一致
+    pub fn apply_snapshot(&self, snapshot: TimerSnapshot) {
+        self.set_label.set(snapshot.label);
+        self.set_kind.set(snapshot.kind);
+        self.set_elapsed_seconds.set((snapshot.elapsed_ms / 1000) as u32);
+        self.set_laps.set(snapshot.laps);
+        self.set_plan.set(snapshot.plan);
+        if let Some(pomodoro) = snapshot.pomodoro {
+            self.set_pomodoro.set(pomodoro);
+        }
+        if let Some(interval) = snapshot.interval {
+            self.set_interval.set(interval);
+        }
+        if let Some(sequence) = snapshot.sequence {
+            self.set_sequence.set(sequence);
+        }
+        self.set_finish.set(snapshot.finish);
+        self.set_overtime_seconds.set((snapshot.overtime_ms / 1000) as u32);
+        let missed = snapshot.missed_deadline_unix_ms;
+        self.set_missed_deadline
+            .set(missed.filter(|_| missed != self.dismissed_missed.get_value()));
+        self.set_total_seconds.set(snapshot.total_seconds);
+        self.set_remaining_seconds.set(snapshot.remaining_seconds);
+        self.set_phase.set(snapshot.phase);
+    }
+
+    // 计算圆环进度：倒计时显示剩余比例，秒表显示相对目标时间的进度（没有目标时为空环）
     pub fn stroke_dashoffset(&self) -> f64 {
         let circumference = 2.0 * std::f64::consts::PI * 100.0;
         let remaining = self.remaining_seconds.with(|value| *value) as f64;
         let total = self.total_seconds.with(|value| *value) as f64;
 
+        if self.kind.with(|kind| *kind) == TimerKind::Stopwatch {
+            let elapsed = self.elapsed_seconds.with(|value| *value) as f64;
+            return if total == 0.0 {
+                circumference
+            } else {
+                circumference * (1.0 - (elapsed / total).min(1.0))
+            };
+        }
+
         if total == 0.0 {
             0.0
         } else {
             circumference * (1.0 - remaining / total)
         }
     }
-}
\ No newline at end of file
+}
+
+// 时长的简短写法，例如“1小时30分”“3分钟”“90秒”
+pub fn format_duration(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    match (hours, minutes, seconds) {
+        (0, 0, seconds) => format!("{}秒", seconds),
+        (0, minutes, 0) => format!("{}分钟", minutes),
+        (0, minutes, seconds) => format!("{}分{}秒", minutes, seconds),
+        (hours, 0, 0) => format!("{}小时", hours),
+        (hours, minutes, 0) => format!("{}小时{}分", hours, minutes),
+        (hours, minutes, seconds) => format!("{}小时{}分{}秒", hours, minutes, seconds),
+    }
+}
+
+// 时长显示为 分:秒 或 时:分:秒，与输入框接受的写法一致
+pub use beep_engine::duration::format_clock;
+
+// 毫秒格式化为保留两位小数的秒
+pub fn format_ms(ms: u64) -> String {
+    format!("{}.{:02}", ms / 1000, ms % 1000 / 10)
+}
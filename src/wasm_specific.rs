@@ -1,9 +1,11 @@
 use leptos::prelude::*;
 use wasm_bindgen::{JsValue, JsCast};
 use wasm_bindgen::closure::Closure;
-use leptos::web_sys::{Event, HtmlInputElement, window, console};
+use leptos::web_sys::{Event, HtmlAnchorElement, HtmlInputElement, VisibilityState, window, console};
 use std::sync::{Arc, Mutex};
-use crate::timer_logic::TimerState;
+use crate::tauri_utils::{call_command, forward_tauri_event};
+use crate::timer_logic::{PresetFormat, TimerEvent, TimerState, TIMER_ID};
+use beep_protocol::{ExportPresets, ExportPresetsArgs, GetTimerState, NoArgs, ProtocolVersion, TimerIdArgs, PROTOCOL_VERSION, TIMER_EVENT};
 use wasm_bindgen_futures::spawn_local;
 use leptos::prelude::request_animation_frame;
 
@@ -25,49 +27,185 @@ pub fn setup_timer_event_listener(timer_state: &Arc<Mutex<TimerState>>) {
 
         // 创建事件回调
         let closure = Closure::wrap(Box::new(move |event: Event| {
-            // 从事件中获取detail属性
-            if let Ok(detail_value) = js_sys::Reflect::get(&event, &JsValue::from_str("detail")) {
-                if let Some(remaining_seconds) = detail_value.as_f64() {
-                    // 转换为u32
-                    let seconds = remaining_seconds as u32;
-                    
-                    // 创建一个新的克隆用于动画帧内部
-                    let timer_state_clone2 = Arc::clone(&timer_state_clone);
-                    
-                    // 使用Leptos的request_animation_frame确保在正确的响应式上下文中更新
-                    request_animation_frame(move || {
-                        // 安全地获取TimerState并更新
-                        if let Ok(mut timer_state) = timer_state_clone2.lock() {
-                            // 在响应式上下文中更新信号
-                            timer_state.set_remaining_seconds.set(seconds);
-                            
-                            // 当倒计时结束时，设置is_running为false
-                            if remaining_seconds == 0.0 {
-                                timer_state.set_is_running.set(false);
-                            }
-                        }
-                    });
+            // 从事件中获取detail属性，并解析为类型化的生命周期事件
+            let detail_value = match js_sys::Reflect::get(&event, &JsValue::from_str("detail")) {
+                Ok(detail_value) => detail_value,
+                Err(_) => return,
+            };
+            let timer_event = match serde_wasm_bindgen::from_value::<TimerEvent>(detail_value) {
+                Ok(timer_event) => timer_event,
+                Err(err) => {
+                    console::error_1(&JsValue::from(format!("无法解析计时器事件: {}", err)));
+                    return;
                 }
+            };
+
+            // 隐藏的标签页不会执行动画帧，此时直接更新，保证到点时状态及时变为结束
+            if document().visibility_state() == VisibilityState::Hidden {
+                if let Ok(timer_state) = timer_state_clone.lock() {
+                    timer_state.apply_event(timer_event);
+                }
+                return;
             }
+
+            // 创建一个新的克隆用于动画帧内部
+            let timer_state_clone2 = Arc::clone(&timer_state_clone);
+
+            // 使用Leptos的request_animation_frame确保在正确的响应式上下文中更新
+            request_animation_frame(move || {
+                // 安全地获取TimerState并更新
+                if let Ok(timer_state) = timer_state_clone2.lock() {
+                    timer_state.apply_event(timer_event);
+                }
+            });
         }) as Box<dyn FnMut(Event)>);
 
         // 获取回调函数引用并添加事件监听器
         let js_callback = closure.as_ref().unchecked_ref::<js_sys::Function>();
-        if let Err(err) = window.add_event_listener_with_callback("timer_update", js_callback) {
+        if let Err(err) = window.add_event_listener_with_callback(TIMER_EVENT, js_callback) {
             console::error_1(&JsValue::from(format!("Failed to add event listener: {:?}", err)));
         }
 
         // 防止闭包被垃圾回收
         closure.forget();
+
+        // 在 Tauri 中，后端事件需要转发到 window 上
+        forward_tauri_event(TIMER_EVENT);
+    }
+}
+
+// 从后端拉取完整快照并覆盖本地状态
+pub fn sync_timer_state(timer_state: &Arc<Mutex<TimerState>>) {
+    let timer_state = Arc::clone(timer_state);
+    call_command::<GetTimerState>(&TimerIdArgs::new(TIMER_ID), move |result| match result {
+        Ok(snapshot) => {
+            if let Ok(timer_state) = timer_state.lock() {
+                timer_state.apply_snapshot(snapshot);
+            }
+        }
+        Err(err) => console::error_1(&JsValue::from(format!("获取计时器状态失败: {}", err))),
+    });
+    if let Ok(timer_state) = timer_state.lock() {
+        timer_state.refresh_alarms();
+        timer_state.refresh_calendars();
+        timer_state.refresh_presets();
+    }
+}
+
+// 启动时检查前后端协议版本，不一致时在界面上明确提示
+pub fn check_protocol_version(timer_state: &Arc<Mutex<TimerState>>) {
+    let (set_error, set_notification_actions) = {
+        let ts = timer_state.lock().unwrap();
+        (ts.set_error, ts.set_notification_actions)
+    };
+    call_command::<ProtocolVersion>(&NoArgs {}, move |result| {
+        if let Ok(info) = &result {
+            set_notification_actions.set(info.notification_actions);
+        }
+        let message = match result {
+            Ok(info) if info.version == PROTOCOL_VERSION => return,
+            Ok(info) => format!("前后端协议版本不一致：前端 v{}，后端 v{}，请重新构建应用", PROTOCOL_VERSION, info.version),
+            Err(err) => format!("无法确认后端协议版本: {}", err),
+        };
+        console::error_1(&JsValue::from(&message));
+        set_error.set(Some(message));
+    });
+}
+
+// 挂载时同步一次，之后在窗口重新获得焦点、重新可见或网络恢复时再次同步
+pub fn setup_state_sync(timer_state: &Arc<Mutex<TimerState>>) {
+    sync_timer_state(timer_state);
+
+    let Some(window) = window() else {
+        return;
+    };
+    let timer_state_clone = Arc::clone(timer_state);
+    let closure = Closure::wrap(Box::new(move |_event: Event| {
+        let visible = document().visibility_state() == VisibilityState::Visible;
+        if visible {
+            sync_timer_state(&timer_state_clone);
+        }
+    }) as Box<dyn FnMut(Event)>);
+
+    let js_callback = closure.as_ref().unchecked_ref::<js_sys::Function>();
+    for event_name in ["focus", "online"] {
+        if let Err(err) = window.add_event_listener_with_callback(event_name, js_callback) {
+            console::error_1(&JsValue::from(format!("Failed to add event listener: {:?}", err)));
+        }
     }
+    if let Err(err) = document().add_event_listener_with_callback("visibilitychange", js_callback) {
+        console::error_1(&JsValue::from(format!("Failed to add event listener: {:?}", err)));
+    }
+
+    // 防止闭包被垃圾回收
+    closure.forget();
 }
 
 // WebAssembly环境下更新总时间的处理函数
-pub fn handle_update_total_time(event: &Event, timer_state: &Arc<Mutex<TimerState>>) {
+pub fn handle_update_total_time(event: &Event, timer_state: &Arc<Mutex<TimerState>>) -> Result<(), String> {
     let target = event.target().and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
-    if let Some(input) = target {
-        if let Ok(parsed_value) = input.value().parse::<u32>() {
-            timer_state.lock().unwrap().update_total_time(parsed_value);
-        }
+    match target {
+        Some(input) => timer_state.lock().unwrap().set_total_from_input(&input.value()),
+        None => Ok(()),
     }
-}
\ No newline at end of file
+}
+// 把文本保存为 CSV 文件（通过下载链接），带 BOM 以便表格软件正确识别中文
+pub fn download_csv(filename: &str, csv: &str) -> Result<(), JsValue> {
+    download_text(filename, "text/csv;charset=utf-8", &format!("\u{feff}{}", csv))
+}
+
+// 通过下载链接把文本保存为文件
+pub fn download_text(filename: &str, mime: &str, text: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(text));
+    let options = leptos::web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = leptos::web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = leptos::web_sys::Url::create_object_url_with_blob(&blob)?;
+    let anchor = document().create_element("a")?.dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    leptos::web_sys::Url::revoke_object_url(&url)
+}
+
+// 导出秒表的圈速表
+pub fn export_laps(timer_state: &Arc<Mutex<TimerState>>) {
+    let csv = timer_state.lock().unwrap().laps_csv();
+    if let Err(err) = download_csv("laps.csv", &csv) {
+        console::error_1(&JsValue::from(format!("导出圈速失败: {:?}", err)));
+    }
+}
+
+// 导出全部预设为 JSON 或 TOML 文件
+pub fn export_presets(timer_state: &TimerState, format: PresetFormat) {
+    let set_error = timer_state.set_error;
+    call_command::<ExportPresets>(&ExportPresetsArgs { format }, move |result| {
+        let result = result.and_then(|text| {
+            let filename = format!("beep-presets.{}", format.extension());
+            download_text(&filename, "text/plain;charset=utf-8", &text).map_err(|err| format!("{:?}", err))
+        });
+        if let Err(err) = result {
+            set_error.set(Some(format!("导出预设失败: {}", err)));
+        }
+    });
+}
+
+// 读取文件选择框中的预设文件并导入，按扩展名判断格式
+pub fn import_presets(event: &Event, timer_state: &TimerState, replace: bool) {
+    let Some(input) = event.target().and_then(|target| target.dyn_into::<HtmlInputElement>().ok()) else {
+        return;
+    };
+    let Some(file) = input.files().and_then(|files| files.get(0)) else {
+        return;
+    };
+    // 清空选择，同一个文件修改后可以再次导入
+    input.set_value("");
+    let format = if file.name().to_lowercase().ends_with(".toml") { PresetFormat::Toml } else { PresetFormat::Json };
+    let timer_state = timer_state.clone();
+    spawn_local(async move {
+        match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+            Ok(text) => timer_state.import_presets(format, text.as_string().unwrap_or_default(), replace),
+            Err(err) => timer_state.set_error.set(Some(format!("读取预设文件失败: {:?}", err))),
+        }
+    });
+}
@@ -0,0 +1,363 @@
+// 纯浏览器环境下的后端：在 wasm 中直接驱动 beep-engine，命令与 Tauri 后端一一对应
+// 计时器、闹钟和预设保存在 localStorage 中，重新打开页面后按截止时刻恢复
+use std::cell::{Cell, RefCell};
+
+use beep_engine::history;
+use beep_engine::preset::{self, Presets};
+use beep_engine::{Clock, Millis, TimerEngine};
+use beep_protocol::{
+    AdjustTimer, Alarm, ConfigureInterval, ConfigurePomodoro, ConfigureSequence, CreateTimer, DeleteAlarm, DeletePreset,
+    DeleteTimer, ExportPresets, ExportSessions, GetTimerState, ImportCalendar, ImportPresets, IpcCommand, LapTimer,
+    LaunchPreset, ListAlarms, ListCalendars, ListPresets, ListSessions, ListTimers, PauseTimer, Ping, ProtocolInfo,
+    ProtocolVersion, RemoveCalendar, ReorderPresets, ResetTimer, ResumeTimer, RunProgram, SaveAlarm, SavePreset, Session,
+    SessionFormat, SetFinishOptions, SetTimerKind, SetTotalSeconds, SnoozeTimer, StartTimer, TimerEvent, TimerKind,
+    TimerSnapshot, TriggerNotification, DEFAULT_TIMER_ID, PROTOCOL_VERSION, TIMER_EVENT,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::tauri_utils::dispatch_window_event;
+use crate::timer_logic::TOTAL_SECONDS;
+
+// 浏览器时钟：performance.now() 是单调的，Date.now() 提供墙上时间
+#[derive(Clone, Copy, Default)]
+pub struct WebClock;
+
+impl Clock for WebClock {
+    fn now_ms(&self) -> Millis {
+        web_sys::window()
+            .and_then(|window| window.performance())
+            .map(|performance| performance.now() as Millis)
+            .unwrap_or_else(|| js_sys::Date::now() as Millis)
+    }
+
+    fn unix_ms(&self) -> i64 {
+        js_sys::Date::now() as i64
+    }
+}
+
+thread_local! {
+    static ENGINE: RefCell<TimerEngine<WebClock>> = RefCell::new(new_engine());
+    static PRESETS: RefCell<Presets> = RefCell::new(load_presets());
+    static TICK_WORKER: Option<web_sys::Worker> = spawn_tick_worker();
+    // 没有 Worker 时，当前等待中的 setTimeout 句柄
+    static WAKEUP: RefCell<Option<i32>> = const { RefCell::new(None) };
+    // 上次写入 localStorage 时计时器和闹钟的版本，没有变化时不重复写入
+    static SAVED_TIMERS: Cell<Option<u64>> = const { Cell::new(None) };
+    static SAVED_ALARMS: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+// localStorage 中保存全部计时器快照的键
+const STORAGE_KEY: &str = "beep.timers";
+// localStorage 中保存闹钟的键
+const ALARMS_KEY: &str = "beep.alarms";
+// localStorage 中保存预设的键
+const PRESETS_KEY: &str = "beep.presets";
+// localStorage 中保存计时历史的键
+const HISTORY_KEY: &str = "beep.history";
+
+fn new_engine() -> TimerEngine<WebClock> {
+    let mut engine = TimerEngine::new(WebClock);
+    engine.on_event(dispatch_timer_event);
+    for snapshot in load::<TimerSnapshot>(STORAGE_KEY) {
+        engine.restore(snapshot);
+    }
+    // 下一次响铃时刻按当前时间重新计算，页面关闭期间错过的闹钟不再补响
+    for alarm in load::<Alarm>(ALARMS_KEY) {
+        let _ = engine.save_alarm(alarm);
+    }
+    if !engine.contains(DEFAULT_TIMER_ID) {
+        let _ = engine.create(Some(DEFAULT_TIMER_ID.to_string()), None, TimerKind::Countdown, TOTAL_SECONDS);
+    }
+    engine
+}
+
+// 第一次打开（还没有保存过预设）时使用默认预设
+fn load_presets() -> Presets {
+    let saved = local_storage().and_then(|storage| storage.get_item(PRESETS_KEY).ok().flatten());
+    Presets::new(if saved.is_some() { load(PRESETS_KEY) } else { preset::defaults() })
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+// 读取上次保存的列表，数据损坏时忽略并从默认状态开始
+fn load<T: DeserializeOwned>(key: &str) -> Vec<T> {
+    let Some(json) = local_storage().and_then(|storage| storage.get_item(key).ok().flatten()) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        web_sys::console::warn_1(&JsValue::from_str(&format!("忽略无法解析的存档 {}: {}", key, e)));
+        Vec::new()
+    })
+}
+
+fn store<T: Serialize>(key: &str, items: &[T]) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let result = serde_json::to_string(items)
+        .map_err(|e| e.to_string())
+        .and_then(|json| storage.set_item(key, &json).map_err(|e| format!("{:?}", e)));
+    if let Err(e) = result {
+        web_sys::console::error_1(&JsValue::from_str(&format!("保存 {} 失败: {}", key, e)));
+    }
+}
+
+// 计时器或闹钟有变化（开始、暂停、到点等，tick 除外）时保存全部快照，运行中的计时器带有截止时刻，
+// 重新打开页面时据此恢复；同时把结束的计时追加到历史中
+fn save_snapshots(engine: &mut TimerEngine<WebClock>) {
+    let timers = engine.timers_revision();
+    if SAVED_TIMERS.with(|saved| saved.replace(Some(timers))) != Some(timers) {
+        store(STORAGE_KEY, &engine.list());
+    }
+    let alarms = engine.alarms_revision();
+    if SAVED_ALARMS.with(|saved| saved.replace(Some(alarms))) != Some(alarms) {
+        store(ALARMS_KEY, &engine.alarms());
+    }
+    let sessions = engine.take_sessions();
+    if !sessions.is_empty() {
+        let mut history = load::<Session>(HISTORY_KEY);
+        history.extend(sessions);
+        store(HISTORY_KEY, &history);
+    }
+}
+
+// 与 Tauri 转发的后端事件形状一致，前端监听逻辑无需区分运行环境
+fn dispatch_timer_event(timer_event: &TimerEvent) {
+    match serde_wasm_bindgen::to_value(timer_event) {
+        Ok(detail) => dispatch_window_event(TIMER_EVENT, &detail),
+        Err(e) => web_sys::console::error_1(&JsValue::from_str(&format!("序列化事件失败: {}", e))),
+    }
+}
+
+// 专用 Worker 中的计时脚本：浏览器会大幅节流隐藏标签页主线程上的定时器，Worker 中的定时器不受影响
+// 收到数字时在该毫秒数后唤醒主线程（按 Worker 自己的截止时刻判断，提前触发时继续等待），收到 null 时取消
+const TICK_WORKER_JS: &str = r#"
+let timer = null;
+let deadline = 0;
+function arm() {
+    timer = setTimeout(() => {
+        if (performance.now() < deadline) {
+            arm();
+            return;
+        }
+        timer = null;
+        postMessage(null);
+    }, Math.max(0, deadline - performance.now()));
+}
+onmessage = (event) => {
+    if (timer !== null) {
+        clearTimeout(timer);
+        timer = null;
+    }
+    if (typeof event.data === 'number') {
+        deadline = performance.now() + event.data;
+        arm();
+    }
+};
+"#;
+
+// 创建计时 Worker，失败时（例如 CSP 禁止 blob: 脚本）退回主线程的 setTimeout
+fn spawn_tick_worker() -> Option<web_sys::Worker> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(TICK_WORKER_JS));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/javascript");
+    let worker = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .and_then(|blob| web_sys::Url::create_object_url_with_blob(&blob))
+        .and_then(|url| web_sys::Worker::new(&url));
+    let worker = match worker {
+        Ok(worker) => worker,
+        Err(e) => {
+            web_sys::console::warn_1(&JsValue::from_str(&format!("无法创建计时Worker，改用setTimeout: {:?}", e)));
+            return None;
+        }
+    };
+    let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(|_: web_sys::MessageEvent| wake());
+    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // Worker 在整个应用生命周期内有效
+    on_message.forget();
+    Some(worker)
+}
+
+// 到达计划时刻：推进引擎、保存状态并安排下一次唤醒
+fn wake() {
+    WAKEUP.with(|wakeup| wakeup.borrow_mut().take());
+    ENGINE.with(|engine| {
+        let mut engine = engine.borrow_mut();
+        engine.poll();
+        save_snapshots(&mut engine);
+    });
+    schedule_wakeup();
+}
+
+// 按引擎给出的下一次 tick 重新安排唤醒
+fn schedule_wakeup() {
+    let next = ENGINE.with(|engine| {
+        let engine = engine.borrow();
+        engine
+            .next_wakeup()
+            .map(|wakeup| wakeup.saturating_sub(engine.clock().now_ms()))
+    });
+
+    let scheduled = TICK_WORKER.with(|worker| {
+        let worker = worker.as_ref()?;
+        let message = next.map_or(JsValue::NULL, |delay| JsValue::from_f64(delay as f64));
+        if let Err(e) = worker.post_message(&message) {
+            web_sys::console::error_1(&JsValue::from_str(&format!("无法通知计时Worker: {:?}", e)));
+        }
+        Some(())
+    });
+    if scheduled.is_some() {
+        return;
+    }
+
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    if let Some(handle) = WAKEUP.with(|wakeup| wakeup.borrow_mut().take()) {
+        window.clear_timeout_with_handle(handle);
+    }
+    let Some(delay) = next else {
+        return;
+    };
+    let handle = window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(Closure::once_into_js(wake).unchecked_ref(), delay as i32)
+        .ok();
+    WAKEUP.with(|wakeup| *wakeup.borrow_mut() = handle);
+}
+
+// 前端以 { args: ... } 的形式传参
+#[derive(Deserialize)]
+struct Incoming<T> {
+    args: T,
+}
+
+fn decode<C: IpcCommand>(args: &JsValue) -> Result<C::Args, String> {
+    serde_wasm_bindgen::from_value::<Incoming<C::Args>>(args.clone())
+        .map(|incoming| incoming.args)
+        .map_err(|e| format!("{} 的参数无效: {}", C::NAME, e))
+}
+
+fn encode<C: IpcCommand>(output: &C::Output) -> Result<JsValue, String> {
+    serde_wasm_bindgen::to_value(output).map_err(|e| format!("无法序列化 {} 的返回值: {}", C::NAME, e))
+}
+
+// 解析参数、执行命令并序列化返回值
+fn run<C: IpcCommand>(
+    args: &JsValue,
+    f: impl FnOnce(&mut TimerEngine<WebClock>, C::Args) -> Result<C::Output, String>,
+) -> Result<JsValue, String> {
+    let args = decode::<C>(args)?;
+    let output = ENGINE.with(|engine| {
+        let mut engine = engine.borrow_mut();
+        let output = f(&mut engine, args);
+        save_snapshots(&mut engine);
+        output
+    })?;
+    schedule_wakeup();
+    encode::<C>(&output)
+}
+
+// 执行预设命令，成功后保存预设
+fn run_presets<C: IpcCommand>(
+    args: &JsValue,
+    f: impl FnOnce(&mut Presets, C::Args) -> Result<C::Output, String>,
+) -> Result<JsValue, String> {
+    let args = decode::<C>(args)?;
+    let output = PRESETS.with(|presets| {
+        let mut presets = presets.borrow_mut();
+        let output = f(&mut presets, args)?;
+        store(PRESETS_KEY, &presets.list());
+        Ok::<_, String>(output)
+    })?;
+    encode::<C>(&output)
+}
+
+// 执行一个后端命令
+pub fn invoke(cmd: &str, args: &JsValue) -> Result<JsValue, String> {
+    match cmd {
+        Ping::NAME => run::<Ping>(args, |_, args| Ok(format!("Pong: {}", args.message))),
+        ProtocolVersion::NAME => run::<ProtocolVersion>(args, |_, _| {
+            // 浏览器中不发送系统通知
+            Ok(ProtocolInfo { version: PROTOCOL_VERSION, notification_actions: false })
+        }),
+        CreateTimer::NAME => run::<CreateTimer>(args, |engine, args| {
+            engine.create(args.id, args.label, args.kind, args.seconds)
+        }),
+        ListTimers::NAME => run::<ListTimers>(args, |engine, _| Ok(engine.list())),
+        GetTimerState::NAME => run::<GetTimerState>(args, |engine, args| engine.snapshot(&args.id)),
+        StartTimer::NAME => run::<StartTimer>(args, |engine, args| engine.start(&args.id)),
+        ResumeTimer::NAME => run::<ResumeTimer>(args, |engine, args| engine.resume(&args.id)),
+        PauseTimer::NAME => run::<PauseTimer>(args, |engine, args| engine.pause(&args.id)),
+        ResetTimer::NAME => run::<ResetTimer>(args, |engine, args| engine.reset(&args.id)),
+        DeleteTimer::NAME => run::<DeleteTimer>(args, |engine, args| engine.remove(&args.id)),
+        SetTotalSeconds::NAME => run::<SetTotalSeconds>(args, |engine, args| engine.set_total(&args.id, args.seconds)),
+        SetTimerKind::NAME => run::<SetTimerKind>(args, |engine, args| engine.set_kind(&args.id, args.kind)),
+        AdjustTimer::NAME => run::<AdjustTimer>(args, |engine, args| engine.adjust(&args.id, args.delta_seconds)),
+        SnoozeTimer::NAME => run::<SnoozeTimer>(args, |engine, args| engine.snooze(&args.id)),
+        SetFinishOptions::NAME => run::<SetFinishOptions>(args, |engine, args| {
+            engine.set_finish_options(&args.id, args.options)
+        }),
+        LapTimer::NAME => run::<LapTimer>(args, |engine, args| engine.lap(&args.id)),
+        ConfigurePomodoro::NAME => run::<ConfigurePomodoro>(args, |engine, args| {
+            engine.configure_pomodoro(&args.id, args.config)
+        }),
+        ConfigureInterval::NAME => run::<ConfigureInterval>(args, |engine, args| {
+            engine.configure_interval(&args.id, args.config)
+        }),
+        ConfigureSequence::NAME => run::<ConfigureSequence>(args, |engine, args| {
+            engine.configure_sequence(&args.id, args.config)
+        }),
+        // 浏览器不能按路径读取程序文件
+        RunProgram::NAME => run::<RunProgram>(args, |_, _| Err("浏览器中无法按路径读取程序文件，请使用桌面版".to_string())),
+        SaveAlarm::NAME => run::<SaveAlarm>(args, |engine, args| engine.save_alarm(args)),
+        ListAlarms::NAME => run::<ListAlarms>(args, |engine, _| Ok(engine.alarms())),
+        DeleteAlarm::NAME => run::<DeleteAlarm>(args, |engine, args| engine.delete_alarm(&args.id)),
+        // 浏览器不能按路径读取本地文件，也就无法在文件变化时重新读取
+        ImportCalendar::NAME => run::<ImportCalendar>(args, |_, _| Err("浏览器中无法导入本地日历文件，请使用桌面版".to_string())),
+        ListCalendars::NAME => run::<ListCalendars>(args, |_, _| Ok(Vec::new())),
+        RemoveCalendar::NAME => run::<RemoveCalendar>(args, |_, args| Err(format!("没有导入过这个日历: {}", args.path))),
+        ListPresets::NAME => run_presets::<ListPresets>(args, |presets, _| Ok(presets.list())),
+        SavePreset::NAME => run_presets::<SavePreset>(args, |presets, args| presets.save(args)),
+        DeletePreset::NAME => run_presets::<DeletePreset>(args, |presets, args| presets.delete(&args.id)),
+        ReorderPresets::NAME => run_presets::<ReorderPresets>(args, |presets, args| presets.reorder(&args.ids)),
+        ExportPresets::NAME => run_presets::<ExportPresets>(args, |presets, args| presets.export(args.format)),
+        ImportPresets::NAME => run_presets::<ImportPresets>(args, |presets, args| {
+            presets.import(args.format, &args.text, args.replace)
+        }),
+        LaunchPreset::NAME => {
+            let seconds = PRESETS.with(|presets| {
+                let preset_id = decode::<LaunchPreset>(args)?.preset_id;
+                presets.borrow().get(&preset_id).map(|preset| preset.seconds)
+            })?;
+            run::<LaunchPreset>(args, |engine, args| engine.start_countdown(&args.id, seconds))
+        }
+        ListSessions::NAME => {
+            let since = decode::<ListSessions>(args)?.since_unix_ms;
+            let sessions: Vec<Session> = load::<Session>(HISTORY_KEY)
+                .into_iter()
+                .filter(|session| since.is_none_or(|since| session.ended_unix_ms >= since))
+                .collect();
+            encode::<ListSessions>(&sessions)
+        }
+        // 浏览器中没有保存对话框，直接以下载的方式保存，返回文件名
+        ExportSessions::NAME => {
+            let args = decode::<ExportSessions>(args)?;
+            let sessions = history::in_range(load::<Session>(HISTORY_KEY), args.from_unix_ms, args.to_unix_ms);
+            let text = history::export(&sessions, args.format, &chrono::Local, js_sys::Date::now() as i64);
+            let filename = format!("beep-history.{}", args.format.extension());
+            let mime = match args.format {
+                SessionFormat::Csv => "text/csv;charset=utf-8",
+                SessionFormat::Ics => "text/calendar;charset=utf-8",
+            };
+            crate::wasm_specific::download_text(&filename, mime, &text).map_err(|e| format!("导出历史失败: {:?}", e))?;
+            encode::<ExportSessions>(&Some(filename))
+        }
+        // 浏览器中没有系统通知命令，倒计时结束事件由界面自行处理
+        TriggerNotification::NAME => run::<TriggerNotification>(args, |_, _| Ok(())),
+        _ => Err(format!("未知命令: {}", cmd)),
+    }
+}